@@ -0,0 +1,232 @@
+//! `fsays` taking the flags of cowsay and cowthink, so it can stand in for
+//! them in scripts
+//!
+//! This happens when the binary is called `cowsay` or `cowthink`, e.g.
+//! through a symlink, or when the first argument is `--compat`.
+
+use std::env;
+use std::ffi::{OsStr, OsString};
+use std::fs;
+use std::io::{stdin, stdout, BufReader, BufWriter, IsTerminal, Read, Write};
+use std::path::{Path, PathBuf};
+
+use clap::{App, Arg};
+use ferris_says::*;
+
+use crate::{
+    list_speakers, speak, user_speakers, Result, ResultExt, ARGS, INPUT, SPEAKER_FILE, STDOUT,
+};
+
+/// Where cowsay looks for `.cow` files unless `COWPATH` says otherwise
+const COWS_DIR: &str = "/usr/share/cowsay/cows";
+
+/// Whether to speak like cowsay or cowthink, if at all
+pub fn mode() -> Option<SpeechModes> {
+    let mut args = env::args_os();
+    let program = PathBuf::from(args.next()?);
+    match program.file_stem().and_then(OsStr::to_str) {
+        Some("cowsay") => return Some(SpeechModes::Say),
+        Some("cowthink") => return Some(SpeechModes::Think),
+        _ => {}
+    }
+
+    match args.next()?.to_str()? {
+        "--compat" | "--compat=cowsay" => Some(SpeechModes::Say),
+        "--compat=cowthink" => Some(SpeechModes::Think),
+        _ => None,
+    }
+}
+
+pub fn run(mode: SpeechModes) -> Result<()> {
+    let mut args: Vec<OsString> = env::args_os().collect();
+    if args.get(1).and_then(|arg| arg.to_str()).is_some_and(|arg| arg.starts_with("--compat")) {
+        args.remove(1);
+    }
+    let program = if mode == SpeechModes::Think { "cowthink" } else { "cowsay" };
+
+    let moods = Mood::all();
+    let mood_helps = moods
+        .iter()
+        .map(|mood| format!("Make the {} face", mood.name()))
+        .collect::<Vec<_>>();
+    let mood_flags = moods
+        .iter()
+        .zip(&mood_helps)
+        .map(|(mood, help)| {
+            Arg::with_name(mood.name())
+                .short(mood.cowsay_flag().to_string())
+                .help(help)
+        })
+        .collect::<Vec<_>>();
+
+    let args = App::new(program)
+        .about("fsays with the flags of cowsay and cowthink")
+        .arg(
+            Arg::with_name("EYES")
+                .short("e")
+                .help("Set the eyes, the first two characters are used")
+                .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("TONGUE")
+                .short("T")
+                .help("Set the tongue, the first two characters are used")
+                .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("SPEAKER")
+                .short("f")
+                .help("Set the speaker, by name or as the path to a .cow or speaker file")
+                .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("LIST")
+                .short("l")
+                .help("List the speakers and cow files there are")
+        )
+        .arg(
+            Arg::with_name("NO_WRAP")
+                .short("n")
+                .help("Keep the lines of the input as they are instead of wrapping them")
+        )
+        .arg(
+            Arg::with_name("WIDTH")
+                .short("W")
+                .help("Set the width of the text box")
+                .takes_value(true)
+                .default_value("40")
+        )
+        .args(&mood_flags)
+        .arg(Arg::with_name("TEXT").multiple(true))
+        .get_matches_from(args);
+
+    let user_speakers = user_speakers()?;
+
+    if args.is_present("LIST") {
+        list_speakers(&user_speakers)?;
+        return list_cows();
+    }
+
+    let width = args.value_of("WIDTH").unwrap().parse().chain_err(|| ARGS)?;
+
+    // Like cowsay, the first mood in the order of its flags wins
+    let mood = moods.iter().find(|mood| args.is_present(mood.name())).cloned();
+
+    let eyes = match args.value_of("EYES") {
+        Some(eyes) => {
+            let mut eyes = two_chars(eyes).map(String::from);
+            Eyes::custom(eyes.next().unwrap(), eyes.next().unwrap())
+        }
+        None => Eyes::RegularEyes,
+    };
+
+    let speaker = match args.value_of("SPEAKER") {
+        Some(name) => find_speaker(name, user_speakers)?,
+        None => Speaker::Cow,
+    };
+
+    // cowsay fills the lines of every paragraph as far as they go
+    let wrapping = Wrapping {
+        line_breaks: if args.is_present("NO_WRAP") {
+            LineBreaks::Verbatim
+        } else {
+            LineBreaks::Paragraphs
+        },
+        algorithm: WrapAlgorithm::FirstFit,
+        ..Default::default()
+    };
+
+    let mut cfg = FerrisConfig {
+        mode,
+        eyes,
+        mood,
+        tongue: args.value_of("TONGUE").map(|tongue| two_chars(tongue).collect()),
+        speaker,
+        wrapping,
+        ..Default::default()
+    };
+
+    let stdin = stdin();
+    let stdout = stdout();
+    let mut writer = BufWriter::new(stdout.lock());
+
+    // The same as without --compat, only what's typed in is trusted
+    if let Some(text) = args.values_of("TEXT") {
+        let text = text.collect::<Vec<&str>>().join(" ");
+        speak(text.as_bytes(), width, &mut writer, &cfg)
+    } else {
+        if !stdin.is_terminal() {
+            cfg.controls = Controls::Strip;
+        }
+        let reader = BufReader::new(stdin.lock())
+            .bytes()
+            .try_fold(Vec::new(), |mut a, b| -> Result<Vec<u8>> {
+                a.push(b.chain_err(|| INPUT)?);
+                Ok(a)
+            })?;
+        speak(&reader, width, &mut writer, &cfg)
+    }
+}
+
+/// The first two characters of `text`, filled up with spaces like cowsay does
+/// for `-e` and `-T`
+fn two_chars(text: &str) -> impl Iterator<Item = char> + '_ {
+    text.chars().chain("  ".chars()).take(2)
+}
+
+/// The directories in `COWPATH`, or where cowsay keeps its cows
+fn cow_dirs() -> Vec<PathBuf> {
+    match env::var_os("COWPATH") {
+        Some(path) => env::split_paths(&path).collect(),
+        None => vec![PathBuf::from(COWS_DIR)],
+    }
+}
+
+/// Looks a speaker up the way cowsay's `-f` does: a path, one of the
+/// speakers `fsays` knows about or a `.cow` file in the cow directories
+fn find_speaker(name: &str, user_speakers: Vec<Speaker>) -> Result<Speaker> {
+    if Path::new(name).components().count() > 1 {
+        return Speaker::from_file(name).chain_err(|| SPEAKER_FILE);
+    }
+    // The cow file cowsay falls back to
+    if name == "default" {
+        return Ok(Speaker::Cow);
+    }
+    if let Some(speaker) = user_speakers.into_iter().find(|speaker| speaker.name() == name) {
+        return Ok(speaker);
+    }
+    if let Ok(speaker) = name.parse() {
+        return Ok(speaker);
+    }
+
+    let file = format!("{}.cow", name);
+    match cow_dirs().into_iter().map(|dir| dir.join(&file)).find(|path| path.is_file()) {
+        Some(path) => Speaker::from_file(path).chain_err(|| SPEAKER_FILE),
+        None => bail!("could not find the {} cow file, see -l for the available ones", name),
+    }
+}
+
+/// Lists the `.cow` files in the cow directories the way cowsay does
+fn list_cows() -> Result<()> {
+    let stdout = stdout();
+    let mut writer = stdout.lock();
+
+    for dir in cow_dirs() {
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(_) => continue,
+        };
+        let mut cows = entries
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.extension() == Some(OsStr::new("cow")))
+            .filter_map(|path| path.file_stem().and_then(OsStr::to_str).map(str::to_owned))
+            .collect::<Vec<_>>();
+        cows.sort();
+
+        writeln!(writer, "Cow files in {}:", dir.display()).chain_err(|| STDOUT)?;
+        writeln!(writer, "{}", cows.join(" ")).chain_err(|| STDOUT)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,264 @@
+//! `fsays list` and `fsays preview`, to find out what `fsays` can draw
+//! without trying every flag
+
+use std::io::{stdout, Write};
+
+use clap::ArgMatches;
+use ferris_says::*;
+
+use crate::{find_speaker, list_speakers, Result, ResultExt, ARGS, STDOUT};
+
+/// What `fsays list` can list
+pub const LISTS: &[&str] = &["speakers", "eyes", "modes", "mouths", "moods", "bubbles"];
+
+/// The room between the pictures of `fsays preview`
+const GAP: usize = 2;
+
+/// `fsays list`, everything or just what was asked for
+pub fn list(what: Option<&str>, json: bool, user_speakers: &[Speaker]) -> Result<()> {
+    let lists: Vec<&str> = match what {
+        Some(what) => vec![what],
+        None => LISTS.to_vec(),
+    };
+
+    let stdout = stdout();
+    let mut writer = stdout.lock();
+
+    if json {
+        let fields = lists
+            .iter()
+            .map(|&what| format!("{}:{}", json_string(what), list_json(what, user_speakers)))
+            .collect::<Vec<_>>();
+        return writeln!(writer, "{{{}}}", fields.join(",")).chain_err(|| STDOUT);
+    }
+
+    for (i, &what) in lists.iter().enumerate() {
+        if i > 0 {
+            writeln!(writer).chain_err(|| STDOUT)?;
+        }
+        // Speakers come with their descriptions and where they were found
+        if what == "speakers" {
+            list_speakers(user_speakers)?;
+            continue;
+        }
+
+        let (title, entries) = match what {
+            "eyes" => (
+                "Eyes:",
+                Eyes::builtin()
+                    .iter()
+                    .map(|eyes| {
+                        let (left, right) = eyes.glyphs();
+                        (eyes.name(), format!("{} {}", left, right))
+                    })
+                    .collect::<Vec<_>>(),
+            ),
+            "modes" => ("Speech modes:", names(SpeechModes::all().iter().map(SpeechModes::name))),
+            "mouths" => ("Mouths:", names(Mouth::all().iter().map(Mouth::name))),
+            "moods" => (
+                "Moods:",
+                Mood::all()
+                    .iter()
+                    .map(|mood| (mood.name(), format!("like cowsay -{}", mood.cowsay_flag())))
+                    .collect(),
+            ),
+            _ => ("Bubble styles:", names(BubbleStyle::builtin().iter().map(BubbleStyle::name))),
+        };
+
+        let name_width = entries.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+        writeln!(writer, "{}", title).chain_err(|| STDOUT)?;
+        for (name, about) in entries {
+            let line = format!("  {:width$}  {}", name, about, width = name_width);
+            writeln!(writer, "{}", line.trim_end()).chain_err(|| STDOUT)?;
+        }
+    }
+
+    Ok(())
+}
+
+// Entries of a list that are just names
+fn names<'a, I: Iterator<Item = &'a str>>(names: I) -> Vec<(&'a str, String)> {
+    names.map(|name| (name, String::new())).collect()
+}
+
+// One of the lists of `fsays list` as a JSON array
+fn list_json(what: &str, user_speakers: &[Speaker]) -> String {
+    let entries: Vec<String> = match what {
+        "speakers" => {
+            let builtin = Speaker::builtin();
+            let speakers = builtin.iter().map(|speaker| (speaker, true));
+            speakers
+                .chain(user_speakers.iter().map(|speaker| (speaker, false)))
+                .map(|(speaker, builtin)| {
+                    format!(
+                        "{{\"name\":{},\"description\":{},\"author\":{},\"builtin\":{}}}",
+                        json_string(speaker.name()),
+                        speaker.description().map_or("null".into(), json_string),
+                        speaker.author().map_or("null".into(), json_string),
+                        builtin
+                    )
+                })
+                .collect()
+        }
+        "eyes" => Eyes::builtin()
+            .iter()
+            .map(|eyes| {
+                let (left, right) = eyes.glyphs();
+                format!(
+                    "{{\"name\":{},\"left\":{},\"right\":{}}}",
+                    json_string(eyes.name()),
+                    json_string(left),
+                    json_string(right)
+                )
+            })
+            .collect(),
+        "modes" => SpeechModes::all().iter().map(|mode| json_string(mode.name())).collect(),
+        "mouths" => Mouth::all().iter().map(|mouth| json_string(mouth.name())).collect(),
+        "moods" => Mood::all()
+            .iter()
+            .map(|mood| {
+                format!(
+                    "{{\"name\":{},\"cowsay_flag\":{}}}",
+                    json_string(mood.name()),
+                    json_string(&mood.cowsay_flag().to_string())
+                )
+            })
+            .collect(),
+        _ => BubbleStyle::builtin().iter().map(|style| json_string(style.name())).collect(),
+    };
+    format!("[{}]", entries.join(","))
+}
+
+/// A picture of `fsays preview` and what it shows
+struct Sample {
+    caption: String,
+    speaker: String,
+    mode: SpeechModes,
+    eyes: Eyes,
+    rendered: Rendered,
+}
+
+/// `fsays preview`, every speaker in every mode or a speaker with all of its
+/// eyes
+pub fn preview(args: &ArgMatches, width: usize, user_speakers: Vec<Speaker>) -> Result<()> {
+    let text = args.values_of("TEXT").unwrap().collect::<Vec<_>>().join(" ");
+    let columns: usize = args.value_of("COLUMNS").unwrap().parse().chain_err(|| ARGS)?;
+
+    let speakers = match args.value_of("SPEAKER") {
+        Some(name) => vec![find_speaker(name, user_speakers)?],
+        None => Speaker::builtin().iter().cloned().chain(user_speakers).collect(),
+    };
+
+    let mut samples = Vec::new();
+    for speaker in speakers {
+        let faces: Vec<(SpeechModes, Eyes)> = if args.is_present("EYES") {
+            Eyes::builtin().iter().map(|eyes| (SpeechModes::Say, eyes.clone())).collect()
+        } else {
+            SpeechModes::all().iter().map(|&mode| (mode, Eyes::RegularEyes)).collect()
+        };
+
+        for (mode, eyes) in faces {
+            let caption = if args.is_present("EYES") {
+                format!("{} {}", speaker.name(), eyes.name())
+            } else {
+                format!("{} {}", speaker.name(), mode.name())
+            };
+            let cfg = FerrisConfig {
+                mode,
+                eyes: eyes.clone(),
+                speaker: speaker.clone(),
+                uppercase: mode == SpeechModes::Shout,
+                ..Default::default()
+            };
+            samples.push(Sample {
+                caption,
+                speaker: speaker.name().to_owned(),
+                mode,
+                eyes,
+                rendered: render(text.as_bytes(), width, &cfg).chain_err(|| ARGS)?,
+            });
+        }
+    }
+
+    let stdout = stdout();
+    let mut writer = stdout.lock();
+
+    if args.is_present("JSON") {
+        let samples = samples
+            .iter()
+            .map(|sample| {
+                let lines = sample.rendered.lines().map(json_string).collect::<Vec<_>>();
+                format!(
+                    "{{\"speaker\":{},\"mode\":{},\"eyes\":{},\"lines\":[{}]}}",
+                    json_string(&sample.speaker),
+                    json_string(sample.mode.name()),
+                    json_string(sample.eyes.name()),
+                    lines.join(",")
+                )
+            })
+            .collect::<Vec<_>>();
+        return writeln!(writer, "[{}]", samples.join(",")).chain_err(|| STDOUT);
+    }
+
+    // As many pictures side by side as fit in the columns, at least one
+    let width = |sample: &Sample| sample.rendered.width().max(sample.caption.chars().count());
+    let mut rows: Vec<Vec<&Sample>> = Vec::new();
+    let mut row_width = 0;
+    for sample in &samples {
+        match rows.last_mut() {
+            Some(row) if row_width + GAP + width(sample) <= columns => {
+                row_width += GAP + width(sample);
+                row.push(sample);
+            }
+            _ => {
+                row_width = width(sample);
+                rows.push(vec![sample]);
+            }
+        }
+    }
+
+    for (i, row) in rows.iter().enumerate() {
+        if i > 0 {
+            writeln!(writer).chain_err(|| STDOUT)?;
+        }
+        // The lines of every picture under its caption, with their widths
+        let cells = row
+            .iter()
+            .map(|sample| {
+                let picture_width = sample.rendered.width();
+                let mut cell = vec![(sample.caption.clone(), sample.caption.chars().count())];
+                cell.extend(sample.rendered.padded_lines().map(|line| (line, picture_width)));
+                (width(sample), cell)
+            })
+            .collect::<Vec<_>>();
+        let height = cells.iter().map(|(_, cell)| cell.len()).max().unwrap_or(0);
+        for line in 0..height {
+            let mut text = String::new();
+            for (width, cell) in &cells {
+                let (part, part_width) = cell.get(line).map_or(("", 0), |(part, width)| (part, *width));
+                text.push_str(part);
+                text.push_str(&" ".repeat(width - part_width + GAP));
+            }
+            writeln!(writer, "{}", text.trim_end()).chain_err(|| STDOUT)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// `text` as a JSON string
+fn json_string(text: &str) -> String {
+    let mut json = String::with_capacity(text.len() + 2);
+    json.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            c if c.is_control() => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
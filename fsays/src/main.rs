@@ -5,12 +5,18 @@ extern crate ferris_says;
 #[macro_use]
 extern crate error_chain;
 
-use clap::{App, Arg};
+use clap::{App, Arg, ArgGroup, ArgMatches, SubCommand};
 use ferris_says::*;
-use std::fs::File;
-use std::io::{stderr, stdin, stdout, BufReader, BufWriter, Read, Write};
+use std::env;
+use std::ffi::OsString;
+use std::fs::{self, File};
+use std::io::{stderr, stdin, stdout, BufReader, BufWriter, IsTerminal, Read, Write};
+use std::path::PathBuf;
 use std::process::exit;
 
+mod compat;
+mod gallery;
+
 error_chain! {}
 
 // Constants used for err messages
@@ -18,6 +24,8 @@ const ARGS: &str = "Invalid argument passed to fsays caused an error";
 const INPUT: &str = "Failed to read input to the program";
 const STDOUT: &str = "Failed to write stdout";
 const STDERR: &str = "Failed to write stderr";
+const SPEAKER_FILE: &str = "Failed to load the speaker file";
+const SPEAKERS_DIR: &str = "Failed to read the speakers directory";
 
 fn main() {
     if let Err(ref e) = run() {
@@ -38,7 +46,46 @@ fn main() {
 }
 
 fn run() -> Result<()> {
-    let args = App::new("Ferris Says")
+    if let Some(mode) = compat::mode() {
+        return compat::run(mode);
+    }
+
+    let hyphenations = Hyphenation::all().iter().map(Hyphenation::name).collect::<Vec<_>>();
+    let eyes = Eyes::builtin().iter().map(Eyes::name).collect::<Vec<_>>();
+    let speech_modes = SpeechModes::all().iter().map(SpeechModes::name).collect::<Vec<_>>();
+    let mouths = Mouth::all().iter().map(Mouth::name).collect::<Vec<_>>();
+    let bubble_styles = BubbleStyle::builtin().iter().map(BubbleStyle::name).collect::<Vec<_>>();
+    let encodings = Encoding::all().iter().map(Encoding::name).collect::<Vec<_>>();
+    let line_breaks = LineBreaks::all().iter().map(LineBreaks::name).collect::<Vec<_>>();
+    let wrap_algorithms = WrapAlgorithm::all().iter().map(WrapAlgorithm::name).collect::<Vec<_>>();
+    let moods = Mood::all();
+    let mood_names = moods.iter().map(Mood::name).collect::<Vec<_>>();
+    let mood_helps = moods
+        .iter()
+        .map(|mood| match mood.cowsay_flag() {
+            flag @ ('s' | 't' | 'w') => format!(
+                "Make the {0} face, the same as --mood {0}, cowsay's -{1} needs --compat",
+                mood.name(),
+                flag
+            ),
+            _ => format!("Make the {0} face, the same as --mood {0}", mood.name()),
+        })
+        .collect::<Vec<_>>();
+    let mood_flags = moods
+        .iter()
+        .zip(&mood_helps)
+        .map(|(mood, help)| {
+            let flag = Arg::with_name(mood.name()).long(mood.name()).help(help);
+            match mood.cowsay_flag() {
+                // Already taken by --speech, --speaker and --width, cowsay's -s, -t
+                // and -w only work with --compat
+                's' | 't' | 'w' => flag,
+                short => flag.short(short.to_string()),
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let app = App::new("Ferris Says")
         .version("0.1")
         .author("Michael Gattozzi <mgattozzi@gmail.com>")
         .about("Prints out input text with Ferris the Rustacean")
@@ -73,7 +120,13 @@ fn run() -> Result<()> {
                 .help("Set speech mode")
                 .takes_value(true)
                 .default_value("say")
-                .possible_values(&["say", "think"])
+                .possible_values(&speech_modes)
+        )
+        .arg(
+            Arg::with_name("UPPERCASE")
+                .long("uppercase")
+                .short("u")
+                .help("Turn the text into capitals, for shouting")
         )
         .arg(
             Arg::with_name("EYES")
@@ -82,100 +135,548 @@ fn run() -> Result<()> {
                 .help("Set eyes")
                 .takes_value(true)
                 .default_value("regular")
-                .possible_values(&[
-                    "regular", "greedy", "happy", "tired", "dead", "youth",
-                    "paranoid", "crying"
-                ])
+                .possible_values(&eyes)
+        )
+        .arg(
+            Arg::with_name("EYES_CUSTOM")
+                .long("eyes-custom")
+                .help("Set eyes of your own, one for both or a left and a right one such as \"^ o\"")
+                .takes_value(true)
+        )
+        .arg(
+            Arg::with_name("MOOD")
+                .long("mood")
+                .help("Set the eyes and the mouth the way cowsay's -b, -d, -g, -p, -s, -t, -w and -y do")
+                .takes_value(true)
+                .possible_values(&mood_names)
+        )
+        .args(&mood_flags)
+        .group(
+            ArgGroup::with_name("MOODS")
+                .arg("MOOD")
+                .args(&mood_names)
+        )
+        .arg(
+            Arg::with_name("MOUTH")
+                .long("mouth")
+                .help("Set the expression of the mouth")
+                .takes_value(true)
+                .default_value("neutral")
+                .possible_values(&mouths)
+        )
+        .arg(
+            Arg::with_name("TONGUE")
+                .long("tongue")
+                .help("Set a tongue of your own, such as \"U \"")
+                .takes_value(true)
         )
         .arg(
             Arg::with_name("SPEAKER")
                 .long("speaker")
                 .short("t")
-                .help("Set antother speaker")
+                .help("Set antother speaker, see fsays list speakers for the choices")
                 .takes_value(true)
                 .default_value("ferris")
-                .possible_values(&[
-                    "ferris", "clippy", "cow"
-                ])
         )
-        .get_matches();
+        .arg(
+            Arg::with_name("SPEAKER_FILE")
+                .long("speaker-file")
+                .help("Load the speaker from a speaker file or a cowsay .cow file")
+                .takes_value(true)
+                .conflicts_with("SPEAKER")
+        )
+        .arg(
+            Arg::with_name("BUBBLE_STYLE")
+                .long("bubble-style")
+                .help("Set the look of the speech bubble")
+                .takes_value(true)
+                .default_value("classic")
+                .possible_values(&bubble_styles)
+        )
+        .arg(
+            Arg::with_name("COLOR")
+                .long("color")
+                .help("Set when to paint Ferris and the bubble, auto skips it for NO_COLOR and non-terminals")
+                .takes_value(true)
+                .default_value("auto")
+                .possible_values(&["auto", "always", "never"])
+        )
+        .arg(
+            Arg::with_name("KEEP_COLORS")
+                .long("keep-colors")
+                .help("Keep the colours of coloured input, e.g. from cargo build --color=always")
+        )
+        .arg(
+            Arg::with_name("CONTROLS")
+                .long("controls")
+                .help("Set what happens to control characters and escapes, auto strips them from files and pipes")
+                .takes_value(true)
+                .default_value("auto")
+                .possible_values(&["auto", "keep", "strip", "escape"])
+        )
+        .arg(
+            Arg::with_name("EFFECT")
+                .long("effect")
+                .help("Paint the text with a rainbow or a gradient, unless colours are off")
+                .takes_value(true)
+                .possible_values(&["rainbow", "diagonal-rainbow", "gradient", "diagonal-gradient"])
+        )
+        .arg(
+            Arg::with_name("SEED")
+                .long("seed")
+                .help("Set the colour the rainbow starts with")
+                .takes_value(true)
+                .default_value("0")
+        )
+        .arg(
+            Arg::with_name("GRADIENT_FROM")
+                .long("gradient-from")
+                .help("Set the first colour of the gradient, a name, 0 to 255 or #rrggbb")
+                .takes_value(true)
+                .default_value("#f74c00")
+        )
+        .arg(
+            Arg::with_name("GRADIENT_TO")
+                .long("gradient-to")
+                .help("Set the last colour of the gradient, a name, 0 to 255 or #rrggbb")
+                .takes_value(true)
+                .default_value("#ffd700")
+        )
+        .arg(
+            Arg::with_name("EFFECT_SPEAKER")
+                .long("effect-speaker")
+                .help("Paint the speaker with the effect as well")
+                .requires("EFFECT")
+        )
+        .arg(
+            Arg::with_name("ENCODING")
+                .long("encoding")
+                .help("Set the encoding of the input")
+                .takes_value(true)
+                .default_value("utf8")
+                .possible_values(&encodings)
+        )
+        .arg(
+            Arg::with_name("LINE_BREAKS")
+                .long("line-breaks")
+                .help("Set how newlines in the input are handled")
+                .takes_value(true)
+                .default_value("wrap")
+                .possible_values(&line_breaks)
+        )
+        .arg(
+            Arg::with_name("HANGING_INDENT")
+                .long("hanging-indent")
+                .help("Indent wrapped lines of list items below the text of the item")
+        )
+        .arg(
+            Arg::with_name("WRAP")
+                .long("wrap")
+                .help("Set how the places to break lines at are picked")
+                .takes_value(true)
+                .default_value("optimal-fit")
+                .possible_values(&wrap_algorithms)
+        )
+        .arg(
+            Arg::with_name("BREAK_WORDS")
+                .long("break-words")
+                .help("Set whether words too long for a line are broken or stick out")
+                .takes_value(true)
+                .default_value("yes")
+                .possible_values(&["yes", "no"])
+        )
+        .arg(
+            Arg::with_name("HYPHENATION")
+                .long("hyphenation")
+                .help("Set where words may be split to fill up a line")
+                .takes_value(true)
+                .default_value("hyphens")
+                .possible_values(&hyphenations)
+        )
+        .arg(
+            Arg::with_name("TAB_WIDTH")
+                .long("tab-width")
+                .help("Set the columns between tab stops")
+                .takes_value(true)
+                .default_value("8")
+        )
+        .arg(
+            Arg::with_name("BIDI")
+                .long("bidi")
+                .help("Lay out right-to-left text such as Hebrew or Arabic and align it to the right")
+        )
+        .arg(
+            Arg::with_name("MIRROR")
+                .long("mirror")
+                .help("Turn the picture around so the speaker stands on the right")
+        )
+        .arg(
+            Arg::with_name("LIST_SPEAKERS")
+                .long("list-speakers")
+                .help("List the built-in speakers and those found in the speakers directory, \
+                       the same as fsays list speakers")
+        )
+        .arg(
+            Arg::with_name("COMPAT")
+                .long("compat")
+                .help("Take the flags of cowsay, or of cowthink with --compat=cowthink, has to come first. \
+                       Running fsays through a symlink named cowsay or cowthink does the same")
+                .takes_value(true)
+                .min_values(0)
+                .require_equals(true)
+                .possible_values(&["cowsay", "cowthink"])
+        )
+        .subcommand(
+            SubCommand::with_name("list")
+                .about("List the speakers, eyes, speech modes, mouths, moods and bubble styles")
+                .arg(
+                    Arg::with_name("WHAT")
+                        .help("List only these")
+                        .possible_values(gallery::LISTS)
+                )
+                .arg(
+                    Arg::with_name("JSON")
+                        .long("json")
+                        .help("Write the lists as JSON")
+                )
+        )
+        .subcommand(
+            SubCommand::with_name("preview")
+                .about("Show every speaker in every speech mode, side by side")
+                .arg(
+                    Arg::with_name("SPEAKER")
+                        .long("speaker")
+                        .help("Show only this speaker")
+                        .takes_value(true)
+                )
+                .arg(
+                    Arg::with_name("EYES")
+                        .long("eyes")
+                        .help("Show every pair of eyes instead of every speech mode")
+                )
+                .arg(
+                    Arg::with_name("WIDTH")
+                        .long("width")
+                        .short("w")
+                        .help("Set the width of the text box, the same as -w of fsays")
+                        .takes_value(true)
+                )
+                .arg(
+                    Arg::with_name("COLUMNS")
+                        .long("columns")
+                        .help("Set the width to fit the pictures into")
+                        .takes_value(true)
+                        .default_value("80")
+                )
+                .arg(
+                    Arg::with_name("JSON")
+                        .long("json")
+                        .help("Write the pictures as JSON")
+                )
+                .arg(
+                    Arg::with_name("TEXT")
+                        .help("What the speakers say")
+                        .multiple(true)
+                        .default_value("Hello!")
+                )
+        );
+    let args = get_matches(app);
+
+    // compat::mode() takes it from the front, anywhere else it's too late
+    if args.is_present("COMPAT") {
+        bail!("--compat has to come first, as in fsays --compat -f tux");
+    }
 
     let width = args.value_of("WIDTH").unwrap().parse().chain_err(|| ARGS)?;
 
     let stdin = stdin();
     let stdout = stdout();
 
-    let mode = match args.value_of("SPEECH").unwrap() {
-        "say" => SpeechModes::Say,
-        "think" => SpeechModes::Think,
-        _ => SpeechModes::Say
+    let user_speakers = user_speakers()?;
+
+    if args.is_present("LIST_SPEAKERS") {
+        return gallery::list(Some("speakers"), false, &user_speakers);
+    }
+
+    match args.subcommand() {
+        ("list", Some(list)) => {
+            return gallery::list(list.value_of("WHAT"), list.is_present("JSON"), &user_speakers)
+        }
+        ("preview", Some(preview)) => {
+            // -w goes for the preview as well as for fsays itself
+            let width = match preview.value_of("WIDTH") {
+                Some(width) => width.parse().chain_err(|| ARGS)?,
+                None => width,
+            };
+            return gallery::preview(preview, width, user_speakers);
+        }
+        _ => {}
+    }
+
+    let mode = args.value_of("SPEECH").unwrap().parse().chain_err(|| ARGS)?;
+
+    let eyes = match args.value_of("EYES_CUSTOM") {
+        Some(eyes) => match eyes.split_whitespace().collect::<Vec<_>>()[..] {
+            [both] => Eyes::custom(both, both),
+            [left, right] => Eyes::custom(left, right),
+            _ => bail!("--eyes-custom takes one or two eyes"),
+        },
+        None => args.value_of("EYES").unwrap().parse().chain_err(|| ARGS)?,
     };
 
-    let eyes = match args.value_of("EYES").unwrap() {
-        "regular" => Eyes::RegularEyes,
-        "greedy" => Eyes::GreedyEyes,
-        "happy" => Eyes::HappyEyes,
-        "tired" => Eyes::TiredEyes,
-        "dead" => Eyes::DeadEyes,
-        "youth" => Eyes::YouthfulEyes,
-        "paranoid" => Eyes::ParanoidEyes,
-        "crying" => Eyes::CryingEyes,
-        _ => Eyes::RegularEyes
+    let mouth = args.value_of("MOUTH").unwrap().parse().chain_err(|| ARGS)?;
+
+    let mood = match args.value_of("MOOD") {
+        Some(name) => Some(name.parse().chain_err(|| ARGS)?),
+        None => moods.iter().find(|mood| args.is_present(mood.name())).cloned(),
     };
 
-    let speaker = match args.value_of("SPEAKER").unwrap() {
-        "ferris" => Speaker::Ferris,
-        "clippy" => Speaker::Clippy,
-        "cow" => Speaker::Cow,
-        _ => Speaker::Ferris
+    let speaker = if let Some(path) = args.value_of("SPEAKER_FILE") {
+        Speaker::from_file(path).chain_err(|| SPEAKER_FILE)?
+    } else {
+        find_speaker(args.value_of("SPEAKER").unwrap(), user_speakers)?
     };
 
-    set_speaker(&speaker).expect("Could not set speaker");
+    let encoding = args.value_of("ENCODING").unwrap().parse().chain_err(|| ARGS)?;
 
-    let cfg = FerrisConfig { mode, eyes };
+    let wrapping = Wrapping {
+        line_breaks: args.value_of("LINE_BREAKS").unwrap().parse().chain_err(|| ARGS)?,
+        hanging_indent: args.is_present("HANGING_INDENT"),
+        algorithm: args.value_of("WRAP").unwrap().parse().chain_err(|| ARGS)?,
+        break_words: args.value_of("BREAK_WORDS") == Some("yes"),
+        hyphenation: args.value_of("HYPHENATION").unwrap().parse().chain_err(|| ARGS)?,
+        tab_width: args.value_of("TAB_WIDTH").unwrap().parse().chain_err(|| ARGS)?,
+        bidi: args.is_present("BIDI"),
+    };
+
+    let bubble = args.value_of("BUBBLE_STYLE").unwrap().parse().chain_err(|| ARGS)?;
+
+    let color_support = color_support(args.value_of("COLOR").unwrap());
+    let palette = if color_support == ColorSupport::None {
+        None
+    } else {
+        Some(speaker.palette())
+    };
+
+    let effect = match args.value_of("EFFECT") {
+        Some(_) if color_support == ColorSupport::None => None,
+        Some(name) => {
+            let colors = if name.ends_with("rainbow") {
+                EffectColors::Rainbow {
+                    seed: args.value_of("SEED").unwrap().parse().chain_err(|| ARGS)?,
+                }
+            } else {
+                EffectColors::Gradient {
+                    from: args.value_of("GRADIENT_FROM").unwrap().parse().chain_err(|| ARGS)?,
+                    to: args.value_of("GRADIENT_TO").unwrap().parse().chain_err(|| ARGS)?,
+                }
+            };
+            let direction = if name.starts_with("diagonal") {
+                Direction::Diagonal
+            } else {
+                Direction::Horizontal
+            };
+            Some(Effect {
+                colors,
+                direction,
+                speaker: args.is_present("EFFECT_SPEAKER"),
+            })
+        }
+        None => None,
+    };
+
+    let mut cfg = FerrisConfig {
+        mode,
+        eyes,
+        mouth,
+        mood,
+        tongue: args.value_of("TONGUE").map(str::to_owned),
+        speaker,
+        encoding,
+        wrapping,
+        bubble,
+        uppercase: args.is_present("UPPERCASE"),
+        palette,
+        color_support,
+        effect,
+        keep_colors: args.is_present("KEEP_COLORS"),
+        controls: Controls::Keep,
+        mirror: args.is_present("MIRROR"),
+    };
+    // Text typed by whoever runs fsays can be trusted, files and pipes can't
+    let controls = |trusted| match args.value_of("CONTROLS").unwrap() {
+        "auto" if trusted => Ok(Controls::Keep),
+        "auto" => Ok(Controls::Strip),
+        name => name.parse().chain_err(|| ARGS),
+    };
 
     let mut writer = BufWriter::new(stdout.lock());
 
     if let Some(files) = args.values_of("FILES") {
+        cfg.controls = controls(false)?;
         // Read in files and say them with Ferris
         let reader = files
             .map(|i| {
                 let reader = BufReader::new(File::open(i).chain_err(|| INPUT)?);
-                Ok(reader
-                    .bytes()
-                    .fold(Ok(Vec::new()), |a: Result<Vec<u8>>, b| {
-                        if let Ok(mut a) = a {
-                            a.push(b.chain_err(|| INPUT)?);
-                            Ok(a)
-                        } else {
-                            a
-                        }
-                    })?)
+                reader.bytes().try_fold(Vec::new(), |mut a, b| {
+                    a.push(b.chain_err(|| INPUT)?);
+                    Ok(a)
+                })
             })
             .collect::<Vec<Result<Vec<u8>>>>();
         for i in reader {
-            perform(&i?, width, &mut writer, &cfg).chain_err(|| STDOUT)?
+            speak(&i?, width, &mut writer, &cfg)?
         }
 
         Ok(())
     } else if let Some(other_args) = args.values_of("TEXT") {
         let s = other_args.collect::<Vec<&str>>().join(" ");
-        perform(s.as_bytes(), width, &mut writer, &cfg).chain_err(|| STDOUT)?;
+        // Arguments are text already, --encoding is about files and stdin
+        cfg.encoding = Encoding::Utf8;
+        cfg.controls = controls(true)?;
+        speak(s.as_bytes(), width, &mut writer, &cfg)?;
         Ok(())
     } else {
-        let reader = BufReader::new(stdin.lock()).bytes().fold(
-            Ok(Vec::new()),
-            |a: Result<Vec<u8>>, b| {
-                if let Ok(mut a) = a {
-                    a.push(b.chain_err(|| INPUT)?);
-                    Ok(a)
-                } else {
-                    a
-                }
-            },
-        )?;
-        perform(&reader, width, &mut writer, &cfg).chain_err(|| STDOUT)?;
+        cfg.controls = controls(stdin.is_terminal())?;
+        let reader = BufReader::new(stdin.lock())
+            .bytes()
+            .try_fold(Vec::new(), |mut a, b| -> Result<Vec<u8>> {
+                a.push(b.chain_err(|| INPUT)?);
+                Ok(a)
+            })?;
+        speak(&reader, width, &mut writer, &cfg)?;
 
         Ok(())
     }
 }
+
+/// The speaker called `name`, one of the user's or a built-in one
+fn find_speaker(name: &str, user_speakers: Vec<Speaker>) -> Result<Speaker> {
+    match user_speakers.into_iter().find(|speaker| speaker.name() == name) {
+        Some(speaker) => Ok(speaker),
+        None => name.parse().chain_err(|| "See fsays list speakers for the available speakers"),
+    }
+}
+
+/// Parses the command line, a `list` or `preview` that is part of the text
+/// such as in `fsays list of things` is said instead of taken as a subcommand
+fn get_matches<'a>(app: App<'a, '_>) -> ArgMatches<'a> {
+    let argv = env::args_os().collect::<Vec<_>>();
+    match app.clone().get_matches_from_safe(&argv) {
+        Ok(args) if args.subcommand_name().is_none() || !args.is_present("TEXT") => args,
+        Err(e) if !e.use_stderr() => e.exit(),
+        result => {
+            // The same again with a `--` in front of the subcommand
+            let at = argv.iter().skip(1).position(|arg| arg == "list" || arg == "preview");
+            let text = at.and_then(|at| {
+                let mut argv = argv.clone();
+                argv.insert(at + 1, OsString::from("--"));
+                app.get_matches_from_safe(argv).ok()
+            });
+            match (text, result) {
+                (Some(args), _) | (None, Ok(args)) => args,
+                (None, Err(e)) => e.exit(),
+            }
+        }
+    }
+}
+
+/// `perform`, telling apart failing to write, input that can't be said and
+/// arguments it can't be said with
+fn speak<W: Write>(input: &[u8], width: usize, writer: &mut W, cfg: &FerrisConfig) -> Result<()> {
+    match perform(input, width, writer, cfg) {
+        Err(e @ ferris_says::Error::Io(_)) => Err(e).chain_err(|| STDOUT),
+        Err(e @ ferris_says::Error::InvalidUtf8 { .. })
+        | Err(e @ ferris_says::Error::InvalidUtf16 { .. }) => Err(e).chain_err(|| INPUT),
+        // Such as a width of 0
+        result => result.chain_err(|| ARGS),
+    }
+}
+
+/// The colours to paint the output with for `--color` set to `when`
+///
+/// `auto` goes by what the terminal supports and paints nothing unless stdout
+/// is a terminal, see `ColorSupport::from_env`. `always` ignores `NO_COLOR` and
+/// uses the basic colours if nothing better is known to work.
+fn color_support(when: &str) -> ColorSupport {
+    match when {
+        "always" => match ColorSupport::from_env(|name| env::var(name).ok().filter(|_| name != "NO_COLOR")) {
+            ColorSupport::None => ColorSupport::Basic,
+            support => support,
+        },
+        "never" => ColorSupport::None,
+        _ if stdout().is_terminal() => ColorSupport::detect(),
+        _ => ColorSupport::None,
+    }
+}
+
+/// Where `fsays` looks for speaker files of the user
+///
+/// This is `fsays/speakers` in `$XDG_CONFIG_HOME`, `~/.config` or `%APPDATA%`
+fn speakers_dir() -> Option<PathBuf> {
+    let config = env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+        .or_else(|| env::var_os("APPDATA").map(PathBuf::from))?;
+    Some(config.join("fsays").join("speakers"))
+}
+
+/// Loads every speaker in the speakers directory, sorted by name
+///
+/// Files that fail to load are reported on stderr and skipped, so one broken
+/// speaker doesn't keep Ferris from talking.
+fn user_speakers() -> Result<Vec<Speaker>> {
+    let dir = match speakers_dir() {
+        Some(ref dir) if dir.is_dir() => dir.clone(),
+        _ => return Ok(Vec::new()),
+    };
+
+    let mut speakers = Vec::new();
+    for entry in fs::read_dir(&dir).chain_err(|| SPEAKERS_DIR)? {
+        let path = entry.chain_err(|| SPEAKERS_DIR)?.path();
+        if !path.is_file() {
+            continue;
+        }
+        match Speaker::from_file(&path) {
+            Ok(speaker) => speakers.push(speaker),
+            Err(e) => writeln!(stderr(), "warning: skipping {}: {}", path.display(), e).expect(STDERR),
+        }
+    }
+    speakers.sort_by(|a, b| a.name().cmp(b.name()));
+
+    Ok(speakers)
+}
+
+fn list_speakers(user_speakers: &[Speaker]) -> Result<()> {
+    let stdout = stdout();
+    let mut writer = stdout.lock();
+
+    let builtin = Speaker::builtin();
+    let name_width = builtin
+        .iter()
+        .chain(user_speakers)
+        .map(|speaker| speaker.name().len())
+        .max()
+        .unwrap_or(0);
+
+    let mut groups = vec![(String::from("Built-in speakers:"), &builtin[..])];
+    if let Some(dir) = speakers_dir() {
+        groups.push((format!("Speakers in {}:", dir.display()), user_speakers));
+    }
+
+    for (title, speakers) in groups {
+        writeln!(writer, "{}", title).chain_err(|| STDOUT)?;
+        for speaker in speakers {
+            let mut line = format!("  {:width$}", speaker.name(), width = name_width);
+            if let Some(description) = speaker.description() {
+                line = format!("{}  {}", line, description);
+            }
+            if let Some(author) = speaker.author() {
+                line = format!("{} (by {})", line, author);
+            }
+            writeln!(writer, "{}", line.trim_end()).chain_err(|| STDOUT)?;
+        }
+    }
+
+    Ok(())
+}
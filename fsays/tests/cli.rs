@@ -0,0 +1,175 @@
+use std::env;
+use std::fs;
+use std::process::{Command, Stdio};
+
+use ferris_says::Mood;
+
+// fsays with `args`, away from the speakers and cows of whoever runs the tests
+fn fsays(args: &[&str]) -> Command {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_fsays"));
+    command
+        .args(args)
+        .env("XDG_CONFIG_HOME", env::temp_dir().join("fsays-cli-test-config"))
+        .env_remove("COWPATH")
+        .stdin(Stdio::null());
+    command
+}
+
+fn stdout(command: &mut Command) -> String {
+    let output = command.output().unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    String::from_utf8(output.stdout).unwrap()
+}
+
+fn lines(command: &mut Command) -> Vec<String> {
+    stdout(command).lines().map(String::from).collect()
+}
+
+#[test]
+fn compat_modes() -> Result<(), ()> {
+    assert_eq!(lines(&mut fsays(&["--compat", "hi"]))[..6], [
+        " ____",
+        "< hi >",
+        " ----",
+        "        \\",
+        "         \\",
+        "            ^__^",
+    ]);
+    assert_eq!(lines(&mut fsays(&["--compat=cowthink", "hi"]))[..5], [
+        " ____",
+        "( hi )",
+        " ----",
+        "        o",
+        "         o",
+    ]);
+    Ok(())
+}
+
+#[test]
+fn compat_faces() -> Result<(), ()> {
+    assert_eq!(lines(&mut fsays(&["--compat", "-e", "^^", "-T", "U", "hi"]))[6..8], [
+        "            (^^)\\_______",
+        "            (__)\\       )\\/\\",
+    ]);
+    // Both are filled up to two characters
+    assert_eq!(lines(&mut fsays(&["--compat", "-e", "o", "-T", "U", "hi"]))[6..9], [
+        "            (o )\\_______",
+        "            (__)\\       )\\/\\",
+        "             U  ||----w |",
+    ]);
+
+    // Every mood letter of cowsay makes the face of its mood
+    for mood in Mood::all().iter() {
+        let flag = format!("-{}", mood.cowsay_flag());
+        assert_eq!(
+            stdout(&mut fsays(&["--compat", &flag, "hi"])),
+            stdout(&mut fsays(&["--speaker", "cow", "--mood", mood.name(), "hi"])),
+            "{}",
+            flag
+        );
+    }
+    assert_eq!(lines(&mut fsays(&["--compat", "-d", "hi"]))[6], "            (xx)\\_______");
+    Ok(())
+}
+
+#[test]
+fn compat_wrapping() -> Result<(), ()> {
+    assert_eq!(lines(&mut fsays(&["--compat", "-W", "10", "one two three four"]))[..4], [
+        " ____________",
+        "/ one two    \\",
+        "\\ three four /",
+        " ------------",
+    ]);
+    assert_eq!(lines(&mut fsays(&["--compat", "-n", "a  b\n  c"]))[..4], [
+        " ______",
+        "/ a  b \\",
+        "\\   c  /",
+        " ------",
+    ]);
+    Ok(())
+}
+
+#[test]
+fn compat_cow_files() -> Result<(), ()> {
+    let cows = env::temp_dir().join("fsays-cli-test-cows");
+    fs::create_dir_all(&cows).unwrap();
+    fs::write(cows.join("box.cow"), "$the_cow = <<EOC;\n  $thoughts\n  [$eyes]\nEOC\n").unwrap();
+
+    let expected = [" ____", "< hi >", " ----", "  \\", "  [oo]"];
+    assert_eq!(lines(fsays(&["--compat", "-f", "box", "hi"]).env("COWPATH", &cows)), expected);
+    let path = cows.join("box.cow");
+    assert_eq!(lines(&mut fsays(&["--compat", "-f", path.to_str().unwrap(), "hi"])), expected);
+    assert_eq!(lines(&mut fsays(&["--compat", "-f", "clippy", "hi"]))[5], "            __");
+
+    let listed = stdout(fsays(&["--compat", "-l"]).env("COWPATH", &cows));
+    assert!(listed.contains("  cow     The classic cowsay cow\n"));
+    assert!(listed.contains(&format!("Cow files in {}:\nbox\n", cows.display())));
+    Ok(())
+}
+
+#[test]
+fn text_that_starts_like_a_subcommand() -> Result<(), ()> {
+    assert_eq!(lines(&mut fsays(&["list", "of", "things"]))[..3], [
+        " ________________",
+        "< list of things >",
+        " ----------------",
+    ]);
+    assert_eq!(lines(&mut fsays(&["hi", "list"]))[1], "< hi list >");
+    Ok(())
+}
+
+#[test]
+fn list_and_preview() -> Result<(), ()> {
+    assert_eq!(stdout(&mut fsays(&["list", "modes", "--json"])), "{\"modes\":[\"say\",\"think\",\"shout\",\"whisper\",\"sing\",\"sleep\"]}\n");
+    assert_eq!(lines(&mut fsays(&["list", "moods"]))[..2], ["Moods:", "  borg      like cowsay -b"]);
+    assert_eq!(stdout(&mut fsays(&["--list-speakers"])), stdout(&mut fsays(&["list", "speakers"])));
+
+    let preview = lines(&mut fsays(&["preview", "--speaker", "cow", "--eyes", "Moo"]));
+    assert_eq!(preview[0], "cow regular                   cow greedy");
+    assert_eq!(preview[2], "< Moo >                       < Moo >");
+    assert_eq!(preview[7], "            (oo)\\_______                  ($$)\\_______");
+    // One cow a row when there's no room for more
+    let narrow = lines(&mut fsays(&["preview", "--speaker", "cow", "--eyes", "--columns", "40"]));
+    assert_eq!(narrow[..3], ["cow regular", " ________", "< Hello! >"]);
+    assert_eq!(narrow[12], "cow greedy");
+
+    // The text is wrapped at -w, given to fsays or to preview
+    let wrapped = [" _______", "/ Hello \\", "\\ there /", " -------"];
+    assert_eq!(lines(&mut fsays(&["-w", "5", "preview", "--speaker", "clippy", "--columns", "10", "Hello", "there"]))[1..5], wrapped);
+    assert_eq!(lines(&mut fsays(&["preview", "-w", "5", "--speaker", "clippy", "--columns", "10", "Hello", "there"]))[1..5], wrapped);
+    Ok(())
+}
+
+#[test]
+fn short_mood_flags() -> Result<(), ()> {
+    // The letters of cowsay that don't mean something else to fsays
+    for mood in Mood::all().iter().filter(|mood| !"stw".contains(mood.cowsay_flag())) {
+        let flag = format!("-{}", mood.cowsay_flag());
+        assert_eq!(stdout(&mut fsays(&[&flag, "hi"])), stdout(&mut fsays(&["--mood", mood.name(), "hi"])), "{}", flag);
+    }
+    assert_eq!(lines(&mut fsays(&["-d", "hi"]))[6], "        \\) /  x x  \\ (/");
+    Ok(())
+}
+
+#[test]
+fn errors() -> Result<(), ()> {
+    let stderr = |args: &[&str]| {
+        let output = fsays(args).env("RUST_BACKTRACE", "0").output().unwrap();
+        assert!(!output.status.success());
+        String::from_utf8(output.stderr).unwrap()
+    };
+
+    // A width of 0 is down to the arguments, not to the input
+    assert_eq!(
+        stderr(&["-w", "0", "hi"]),
+        "error: Invalid argument passed to fsays caused an error\ncaused by: can't wrap text to a width of 0\n"
+    );
+    assert_eq!(
+        stderr(&["-t", "clippy", "--compat", "hi"]),
+        "error: --compat has to come first, as in fsays --compat -f tux\n"
+    );
+    let latin1 = env::temp_dir().join("fsays-cli-test-latin1.txt");
+    fs::write(&latin1, b"caf\xe9").unwrap();
+    assert!(stderr(&["-f", latin1.to_str().unwrap()]).starts_with("error: Failed to read input to the program\n"));
+    Ok(())
+}
@@ -0,0 +1,243 @@
+use std::borrow::Cow;
+use std::str::FromStr;
+
+use super::{Error, Result};
+
+/// What happens to control characters and escape sequences in the text
+///
+/// Text from elsewhere, such as chat messages or CI logs, can carry escape
+/// sequences that move the cursor, set the title of the terminal or clear
+/// the screen. Newlines and tabs are always kept, and with
+/// `FerrisConfig::keep_colors` so are SGR colour sequences.
+///
+/// # Example
+///
+/// ```rust
+/// use ferris_says::*;
+///
+/// let cfg = FerrisConfig { controls: Controls::Escape, ..Default::default() };
+/// let rendered = render(b"Hi\x1b[2J", 40, &cfg).unwrap();
+/// assert_eq!(rendered.lines().nth(1), Some("< Hi^[[2J >"));
+/// ```
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Controls {
+    /// Copy them as they are, for text that can be trusted
+    #[default]
+    Keep,
+    /// Leave them out
+    Strip,
+    /// Show them in caret notation, e.g. `^[` for escape
+    Escape,
+}
+
+impl Controls {
+    /// The name used to pick it, e.g. on the command line
+    pub fn name(&self) -> &'static str {
+        match self {
+            Controls::Keep => "keep",
+            Controls::Strip => "strip",
+            Controls::Escape => "escape",
+        }
+    }
+
+    /// All the ways to handle control characters
+    pub fn all() -> [Controls; 3] {
+        [Controls::Keep, Controls::Strip, Controls::Escape]
+    }
+}
+
+impl FromStr for Controls {
+    type Err = Error;
+
+    /// Look up a way to handle control characters by its `name`
+    fn from_str(name: &str) -> Result<Controls> {
+        Controls::all()
+            .iter()
+            .find(|controls| controls.name() == name)
+            .cloned()
+            .ok_or_else(|| Error::UnknownControls(name.to_owned()))
+    }
+}
+
+/// Turns every colour and style off again
+pub(crate) const RESET: &str = "\x1b[0m";
+
+/// The SGR sequence, such as `\x1b[1;31m`, at the very start of `text`
+fn sgr_at(text: &str) -> Option<&str> {
+    let params = text.strip_prefix("\x1b[")?;
+    let len = params.find(|c: char| !(c.is_ascii_digit() || c == ';' || c == ':'))?;
+    if params[len..].starts_with('m') {
+        Some(&text[..len + 3])
+    } else {
+        None
+    }
+}
+
+/// `text` in capitals, leaving its SGR sequences alone
+pub(crate) fn to_uppercase(text: &str) -> String {
+    let mut upper = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('\x1b') {
+        upper.push_str(&rest[..start].to_uppercase());
+        let len = sgr_at(&rest[start..]).map_or(1, str::len);
+        upper.push_str(&rest[start..start + len]);
+        rest = &rest[start + len..];
+    }
+    upper + &rest.to_uppercase()
+}
+
+/// Make every line stand on its own: the colours still on at the end of a
+/// line are reset there and turned on again at the start of the next one
+///
+/// That way the colours of the text never leak into the frame of the bubble.
+pub(crate) fn balance(lines: &mut [String]) {
+    // The sequences since the last reset
+    let mut open = String::new();
+    for line in lines.iter_mut() {
+        let mut balanced = open.clone();
+        let mut rest = line.as_str();
+        while let Some(start) = rest.find("\x1b[") {
+            balanced.push_str(&rest[..start]);
+            rest = &rest[start..];
+            match sgr_at(rest) {
+                Some(sgr) => {
+                    let params = &sgr[2..sgr.len() - 1];
+                    if params.is_empty() || params == "0" {
+                        open.clear();
+                    } else {
+                        if params.starts_with("0;") {
+                            open.clear();
+                        }
+                        open.push_str(sgr);
+                    }
+                    balanced.push_str(sgr);
+                    rest = &rest[sgr.len()..];
+                }
+                None => {
+                    balanced.push('\x1b');
+                    rest = &rest[1..];
+                }
+            }
+        }
+        balanced.push_str(rest);
+        if !open.is_empty() {
+            balanced.push_str(RESET);
+        }
+        *line = balanced;
+    }
+}
+
+/// `text` with its control characters and escape sequences handled the way
+/// `controls` says, except for SGR sequences if `keep_colors` is set
+pub(crate) fn sanitize(text: &str, controls: Controls, keep_colors: bool) -> Cow<'_, str> {
+    let is_kept = |c: char| c == '\n' || c == '\t' || !c.is_control();
+    if controls == Controls::Keep || text.chars().all(is_kept) {
+        return Cow::Borrowed(text);
+    }
+
+    let mut clean = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find(|c: char| !is_kept(c)) {
+        clean.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let len = match sgr_at(rest) {
+            Some(sgr) if keep_colors => {
+                clean.push_str(sgr);
+                rest = &rest[sgr.len()..];
+                continue;
+            }
+            _ if rest.starts_with("\r\n") => {
+                clean.push('\n');
+                rest = &rest[2..];
+                continue;
+            }
+            _ => sequence_len(rest),
+        };
+        if controls == Controls::Escape {
+            for c in rest[..len].chars() {
+                escape(&mut clean, c);
+            }
+        }
+        rest = &rest[len..];
+    }
+    clean.push_str(rest);
+    Cow::Owned(clean)
+}
+
+// The length of the control character or escape sequence at the start of
+// `text`, so a sequence is handled as a whole. Sequences that never end take
+// the rest of the text.
+pub(crate) fn sequence_len(text: &str) -> usize {
+    let mut chars = text.char_indices();
+    let kind = match chars.next() {
+        Some((_, '\x1b')) => match chars.next() {
+            Some((_, c)) if (' '..='~').contains(&c) => c,
+            _ => return 1,
+        },
+        // The C1 controls that start a sequence of their own
+        Some((_, '\u{9b}')) => '[',
+        Some((_, '\u{9d}')) => ']',
+        Some((_, '\u{90}')) => 'P',
+        Some((_, '\u{98}')) => 'X',
+        Some((_, '\u{9e}')) => '^',
+        Some((_, '\u{9f}')) => '_',
+        Some((_, c)) => return c.len_utf8(),
+        None => return 0,
+    };
+
+    match kind {
+        // Control sequences, parameters and intermediates up to a final byte
+        '[' => {
+            for (i, c) in chars {
+                if ('@'..='~').contains(&c) {
+                    return i + 1;
+                } else if !(' '..='?').contains(&c) {
+                    return i;
+                }
+            }
+        }
+        // Strings such as the title of the window, up to BEL or the string
+        // terminator
+        ']' | 'P' | 'X' | '^' | '_' => {
+            for (i, c) in chars {
+                match c {
+                    '\x07' | '\u{9c}' => return i + c.len_utf8(),
+                    '\x1b' if text[i + 1..].starts_with('\\') => return i + 2,
+                    '\x1b' => return i,
+                    _ => {}
+                }
+            }
+        }
+        // Intermediates up to a final byte
+        ' '..='/' => {
+            for (i, c) in chars {
+                if ('0'..='~').contains(&c) {
+                    return i + 1;
+                } else if !(' '..='/').contains(&c) {
+                    return i;
+                }
+            }
+        }
+        // Escape and a single final byte
+        _ => return 2,
+    }
+    text.len()
+}
+
+// Write `c` so it can be seen, control characters in caret notation
+fn escape(out: &mut String, c: char) {
+    match c as u32 {
+        code @ 0..=0x1f => {
+            out.push('^');
+            out.push((code as u8 + 0x40) as char);
+        }
+        0x7f => out.push_str("^?"),
+        // A C1 control is short for escape and a character
+        code @ 0x80..=0x9f => {
+            out.push_str("^[");
+            out.push((code as u8 - 0x40) as char);
+        }
+        _ => out.push(c),
+    }
+}
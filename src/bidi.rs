@@ -0,0 +1,37 @@
+use unicode_bidi::{get_base_direction, Direction, Level, ParagraphBidiInfo};
+
+use super::ansi::sanitize;
+use super::width::display_width;
+use super::Controls;
+
+/// Whether the paragraph `text` runs from right to left, which is up to its
+/// first letter with a strong direction
+pub(crate) fn is_rtl(text: &str) -> bool {
+    get_base_direction(text) == Direction::Rtl
+}
+
+/// Put `lines` in the order their characters are displayed in and align the
+/// lines of right-to-left paragraphs, as told by `rtl`, to the right
+///
+/// Terminals draw characters left to right as they come, so the bidi
+/// algorithm is applied here to get right-to-left text across. Colours can't
+/// follow the characters around and are left out of lines that need
+/// reordering.
+pub(crate) fn lay_out(lines: &mut [String], rtl: &[bool]) {
+    for (line, &rtl) in lines.iter_mut().zip(rtl) {
+        let level = if rtl { Level::rtl() } else { Level::ltr() };
+        if !ParagraphBidiInfo::new(line, Some(level)).has_rtl() {
+            continue;
+        }
+        let plain = sanitize(line, Controls::Strip, false);
+        let info = ParagraphBidiInfo::new(&plain, Some(level));
+        *line = info.reorder_line(0..plain.len()).into_owned();
+    }
+
+    let width = lines.iter().map(|line| display_width(line)).max().unwrap_or(0);
+    for (line, &rtl) in lines.iter_mut().zip(rtl) {
+        if rtl {
+            line.insert_str(0, &" ".repeat(width - display_width(line)));
+        }
+    }
+}
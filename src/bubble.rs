@@ -0,0 +1,281 @@
+use std::fmt;
+use std::str::FromStr;
+
+use super::color::{paint, Palette};
+use super::effect::Brush;
+use super::width::display_width;
+use super::{Error, Result, SpeechModes};
+
+/// The pieces a speech bubble is put together from
+///
+/// The top and bottom bars repeat `top` and `bottom` over the width of the
+/// text plus two columns, so those should be a single column wide. The sides
+/// include the space between the frame and the text. A bubble of one line
+/// uses `only_line`, longer ones use `first_line`, `middle_line` and
+/// `last_line`.
+///
+/// # Example
+///
+/// ```rust
+/// use ferris_says::*;
+///
+/// const STARS: Frame = Frame {
+///     top_left: "*", top: "*", top_right: "*",
+///     bottom_left: "*", bottom: "*", bottom_right: "*",
+///     only_line: ["* ", " *"],
+///     first_line: ["* ", " *"],
+///     middle_line: ["* ", " *"],
+///     last_line: ["* ", " *"],
+/// };
+/// let cfg = FerrisConfig { bubble: BubbleStyle::Custom(&STARS), ..Default::default() };
+/// let rendered = render(b"Hi", 10, &cfg).unwrap();
+/// assert_eq!(rendered.lines().nth(1), Some("* Hi *"));
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Frame {
+    pub top_left: &'static str,
+    pub top: &'static str,
+    pub top_right: &'static str,
+    pub bottom_left: &'static str,
+    pub bottom: &'static str,
+    pub bottom_right: &'static str,
+    /// Left and right side of the only line
+    pub only_line: [&'static str; 2],
+    /// Left and right side of the first line
+    pub first_line: [&'static str; 2],
+    /// Left and right side of all lines between the first and the last
+    pub middle_line: [&'static str; 2],
+    /// Left and right side of the last line
+    pub last_line: [&'static str; 2],
+}
+
+/// The look of the speech bubble
+///
+/// The classic bubble changes with the speech mode, it turns into the `( )`
+/// cloud of cowthink when thinking or sleeping, gets spikes when shouting,
+/// dots when whispering and notes when singing. The other styles are drawn as
+/// they are.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum BubbleStyle {
+    /// The ASCII bubble of cowsay
+    #[default]
+    Classic,
+    /// Box drawing with single lines, `┌─┐`
+    Single,
+    /// Box drawing with double lines, `╔═╗`
+    Double,
+    /// Box drawing with single lines and round corners, `╭─╮`
+    Rounded,
+    /// Box drawing with heavy lines, `┏━┓`
+    Heavy,
+    /// A cloud with a scalloped edge, `⁀⁀⁀`
+    Cloud,
+    /// A frame of your own
+    Custom(&'static Frame),
+}
+
+const CLASSIC: Frame = Frame {
+    top_left: " ",
+    top: "_",
+    top_right: "",
+    bottom_left: " ",
+    bottom: "-",
+    bottom_right: "",
+    only_line: ["< ", " >"],
+    first_line: ["/ ", " \\"],
+    middle_line: ["| ", " |"],
+    last_line: ["\\ ", " /"],
+};
+
+// The bubble of cowthink
+const THOUGHT: Frame = Frame {
+    top_left: " ",
+    top: "_",
+    top_right: "",
+    bottom_left: " ",
+    bottom: "-",
+    bottom_right: "",
+    only_line: ["( ", " )"],
+    first_line: ["( ", " )"],
+    middle_line: ["( ", " )"],
+    last_line: ["( ", " )"],
+};
+
+const SPIKY: Frame = Frame {
+    top_left: " ",
+    top: "^",
+    top_right: "",
+    bottom_left: " ",
+    bottom: "v",
+    bottom_right: "",
+    only_line: ["< ", " >"],
+    first_line: ["< ", " >"],
+    middle_line: ["< ", " >"],
+    last_line: ["< ", " >"],
+};
+
+const DOTTED: Frame = Frame {
+    top_left: " ",
+    top: ".",
+    top_right: "",
+    bottom_left: " ",
+    bottom: "'",
+    bottom_right: "",
+    only_line: [": ", " :"],
+    first_line: [": ", " :"],
+    middle_line: [": ", " :"],
+    last_line: [": ", " :"],
+};
+
+const MUSICAL: Frame = Frame {
+    top_left: " ",
+    top: "_",
+    top_right: "",
+    bottom_left: " ",
+    bottom: "-",
+    bottom_right: "",
+    only_line: ["♪ ", " ♫"],
+    first_line: ["♪ ", " ♫"],
+    middle_line: ["♪ ", " ♫"],
+    last_line: ["♪ ", " ♫"],
+};
+
+const CLOUD: Frame = Frame {
+    top_left: " ",
+    top: "⁀",
+    top_right: "",
+    bottom_left: " ",
+    bottom: "‿",
+    bottom_right: "",
+    only_line: ["( ", " )"],
+    first_line: ["( ", " )"],
+    middle_line: ["( ", " )"],
+    last_line: ["( ", " )"],
+};
+
+const SINGLE: Frame = box_frame(["┌", "─", "┐"], ["└", "─", "┘"], ["│ ", " │"]);
+const DOUBLE: Frame = box_frame(["╔", "═", "╗"], ["╚", "═", "╝"], ["║ ", " ║"]);
+const ROUNDED: Frame = box_frame(["╭", "─", "╮"], ["╰", "─", "╯"], ["│ ", " │"]);
+const HEAVY: Frame = box_frame(["┏", "━", "┓"], ["┗", "━", "┛"], ["┃ ", " ┃"]);
+
+// A box looks the same on every line
+const fn box_frame(top: [&'static str; 3], bottom: [&'static str; 3], sides: [&'static str; 2]) -> Frame {
+    Frame {
+        top_left: top[0],
+        top: top[1],
+        top_right: top[2],
+        bottom_left: bottom[0],
+        bottom: bottom[1],
+        bottom_right: bottom[2],
+        only_line: sides,
+        first_line: sides,
+        middle_line: sides,
+        last_line: sides,
+    }
+}
+
+impl BubbleStyle {
+    /// The pieces the bubble is drawn with in `mode`
+    pub fn frame(&self, mode: SpeechModes) -> &Frame {
+        match (self, mode) {
+            (BubbleStyle::Classic, SpeechModes::Say) => &CLASSIC,
+            (BubbleStyle::Classic, SpeechModes::Think) => &THOUGHT,
+            (BubbleStyle::Classic, SpeechModes::Sleep) => &THOUGHT,
+            (BubbleStyle::Classic, SpeechModes::Shout) => &SPIKY,
+            (BubbleStyle::Classic, SpeechModes::Whisper) => &DOTTED,
+            (BubbleStyle::Classic, SpeechModes::Sing) => &MUSICAL,
+            (BubbleStyle::Single, _) => &SINGLE,
+            (BubbleStyle::Double, _) => &DOUBLE,
+            (BubbleStyle::Rounded, _) => &ROUNDED,
+            (BubbleStyle::Heavy, _) => &HEAVY,
+            (BubbleStyle::Cloud, _) => &CLOUD,
+            (BubbleStyle::Custom(frame), _) => frame,
+        }
+    }
+
+    /// The name used to pick the style, e.g. on the command line
+    pub fn name(&self) -> &'static str {
+        match self {
+            BubbleStyle::Classic => "classic",
+            BubbleStyle::Single => "single",
+            BubbleStyle::Double => "double",
+            BubbleStyle::Rounded => "rounded",
+            BubbleStyle::Heavy => "heavy",
+            BubbleStyle::Cloud => "cloud",
+            BubbleStyle::Custom(_) => "custom",
+        }
+    }
+
+    /// The built-in styles
+    pub fn builtin() -> [BubbleStyle; 6] {
+        [
+            BubbleStyle::Classic,
+            BubbleStyle::Single,
+            BubbleStyle::Double,
+            BubbleStyle::Rounded,
+            BubbleStyle::Heavy,
+            BubbleStyle::Cloud,
+        ]
+    }
+}
+
+impl FromStr for BubbleStyle {
+    type Err = Error;
+
+    /// Look up a built-in style by its `name`
+    fn from_str(name: &str) -> Result<BubbleStyle> {
+        BubbleStyle::builtin()
+            .iter()
+            .find(|style| style.name() == name)
+            .cloned()
+            .ok_or_else(|| Error::UnknownBubbleStyle(name.to_owned()))
+    }
+}
+
+/// Draw `lines` in a bubble, padded to the widest of them
+///
+/// The text is painted with `brush` if there is one, otherwise with the text
+/// colour of `palette`.
+pub(crate) fn draw<W>(
+    out: &mut W,
+    lines: &[&str],
+    frame: &Frame,
+    palette: &Palette,
+    brush: Option<&Brush>,
+) -> fmt::Result
+where
+    W: fmt::Write,
+{
+    let width = lines.iter().map(|line| display_width(line)).max().unwrap_or(0);
+
+    let top = format!("{}{}{}", frame.top_left, frame.top.repeat(width + 2), frame.top_right);
+    paint(out, palette.frame, &top)?;
+    out.write_char('\n')?;
+
+    for (i, line) in lines.iter().enumerate() {
+        let [left, right] = if lines.len() == 1 {
+            frame.only_line
+        } else if i == 0 {
+            frame.first_line
+        } else if i == lines.len() - 1 {
+            frame.last_line
+        } else {
+            frame.middle_line
+        };
+
+        paint(out, palette.frame, left)?;
+        match brush {
+            Some(brush) => brush.paint_line(out, line, i, (width, lines.len()))?,
+            None => paint(out, palette.text, line)?,
+        }
+        for _ in display_width(line)..width {
+            out.write_char(' ')?;
+        }
+        paint(out, palette.frame, right)?;
+        out.write_char('\n')?;
+    }
+
+    let bottom = format!("{}{}{}", frame.bottom_left, frame.bottom.repeat(width + 2), frame.bottom_right);
+    paint(out, palette.frame, &bottom)?;
+    out.write_char('\n')
+}
@@ -0,0 +1,172 @@
+use std::env;
+
+use super::color::BASIC;
+use super::{Color, Palette};
+
+/// The colours a terminal can show
+///
+/// Colours the terminal can't show are replaced by the closest one it can
+/// before the picture is painted.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ColorSupport {
+    /// No colours at all
+    None,
+    /// The sixteen basic colours
+    Basic,
+    /// The 256 colours of xterm
+    Ansi256,
+    /// Any red, green and blue
+    #[default]
+    TrueColor,
+}
+
+impl ColorSupport {
+    /// Find out what the terminal supports from the environment of the process
+    ///
+    /// See `from_env`.
+    pub fn detect() -> ColorSupport {
+        ColorSupport::from_env(|name| env::var(name).ok())
+    }
+
+    /// Find out what the terminal supports from environment variables
+    ///
+    /// `var` looks up a variable. `NO_COLOR` set to anything turns colours off,
+    /// `COLORTERM` set to `truecolor` or `24bit` turns all of them on,
+    /// otherwise `TERM` decides: `dumb` or nothing at all means no colours,
+    /// names ending in `-256color` get 256 colours, names ending in `-direct`
+    /// or `-truecolor` get all of them and anything else the basic sixteen.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ferris_says::*;
+    ///
+    /// let support = ColorSupport::from_env(|name| match name {
+    ///     "TERM" => Some(String::from("xterm-256color")),
+    ///     _ => None,
+    /// });
+    /// assert_eq!(support, ColorSupport::Ansi256);
+    /// ```
+    pub fn from_env<F>(var: F) -> ColorSupport
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        if var("NO_COLOR").is_some_and(|no_color| !no_color.is_empty()) {
+            return ColorSupport::None;
+        }
+        if let Some("truecolor") | Some("24bit") = var("COLORTERM").as_deref() {
+            return ColorSupport::TrueColor;
+        }
+
+        match var("TERM") {
+            None => ColorSupport::None,
+            Some(ref term) if term.is_empty() || term == "dumb" => ColorSupport::None,
+            Some(ref term) if term.ends_with("-direct") || term.ends_with("-truecolor") => {
+                ColorSupport::TrueColor
+            }
+            Some(ref term) if term.ends_with("-256color") => ColorSupport::Ansi256,
+            Some(_) => ColorSupport::Basic,
+        }
+    }
+}
+
+impl Color {
+    /// The closest colour that can be shown with `support`, if any
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ferris_says::*;
+    ///
+    /// assert_eq!(FERRIS_ORANGE.downsample(ColorSupport::Ansi256), Some(Color::Ansi256(202)));
+    /// assert_eq!(FERRIS_ORANGE.downsample(ColorSupport::Basic), Some(Color::BrightRed));
+    /// assert_eq!(FERRIS_ORANGE.downsample(ColorSupport::None), None);
+    /// ```
+    pub fn downsample(self, support: ColorSupport) -> Option<Color> {
+        match (support, self) {
+            (ColorSupport::None, _) => None,
+            (ColorSupport::TrueColor, color) => Some(color),
+            (_, color) if color.basic_index().is_some() => Some(color),
+            (ColorSupport::Ansi256, Color::Ansi256(index)) => Some(Color::Ansi256(index)),
+            (ColorSupport::Ansi256, color) => Some(Color::Ansi256(nearest_ansi256(color.rgb()))),
+            (ColorSupport::Basic, Color::Ansi256(index)) if index < 16 => Some(BASIC[index as usize]),
+            (ColorSupport::Basic, color) => Some(nearest_basic(color.rgb())),
+        }
+    }
+
+    /// Red, green and blue of the colour, for the basic colours as xterm
+    /// shows them by default
+    pub fn rgb(self) -> (u8, u8, u8) {
+        match self {
+            Color::Rgb(r, g, b) => (r, g, b),
+            Color::Ansi256(index) if index < 16 => BASIC_RGB[index as usize],
+            Color::Ansi256(index) if index < 232 => {
+                let index = index - 16;
+                let level = |i: u8| CUBE_LEVELS[i as usize];
+                (level(index / 36), level(index / 6 % 6), level(index % 6))
+            }
+            Color::Ansi256(index) => {
+                let gray = 8 + (index - 232) * 10;
+                (gray, gray, gray)
+            }
+            basic => BASIC_RGB[basic.basic_index().unwrap() as usize],
+        }
+    }
+}
+
+impl Palette {
+    /// The palette with every colour replaced by the closest one that can be
+    /// shown with `support`
+    pub fn downsample(&self, support: ColorSupport) -> Palette {
+        let downsample = |color: Option<Color>| color.and_then(|color| color.downsample(support));
+        Palette {
+            frame: downsample(self.frame),
+            text: downsample(self.text),
+            tail: downsample(self.tail),
+            speaker: downsample(self.speaker),
+        }
+    }
+}
+
+// The default colours of xterm
+const BASIC_RGB: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+// The steps of each channel in the 6×6×6 cube of the 256 colours
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+fn distance((r1, g1, b1): (u8, u8, u8), (r2, g2, b2): (u8, u8, u8)) -> u32 {
+    let channel = |a: u8, b: u8| (i32::from(a) - i32::from(b)).pow(2) as u32;
+    channel(r1, r2) + channel(g1, g2) + channel(b1, b2)
+}
+
+fn nearest_basic(rgb: (u8, u8, u8)) -> Color {
+    (0..16)
+        .min_by_key(|&index| distance(rgb, BASIC_RGB[index]))
+        .map(|index| BASIC[index])
+        .unwrap()
+}
+
+// Only the cube and the grays are considered, the first sixteen colours
+// depend on the theme of the terminal
+fn nearest_ansi256(rgb: (u8, u8, u8)) -> u8 {
+    (16..=255)
+        .min_by_key(|&index| distance(rgb, Color::Ansi256(index).rgb()))
+        .unwrap()
+}
@@ -0,0 +1,479 @@
+use std::borrow::Cow;
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use super::color::{self, Color, Palette, FERRIS_ORANGE};
+use super::width::{clusters, display_width};
+use super::{Error, Mouth, Result, Speaker};
+
+/// Something that can stand below the speech bubble
+///
+/// A character is described by a template of its art. The template is drawn
+/// as is, except for the following placeholders:
+///
+/// * `{tail}` the tail of the bubble, usually two of these on the first lines
+///   leading from the bubble to the character
+/// * `{left_eye}` and `{right_eye}` where the eyes go
+/// * `{tongue}` the tongue, filled with `tongue()` by default
+/// * `{mouth}` the mouth, filled with `mouth()` by default
+/// * `{accessory}` where things the speaker wears in some moods go, see
+///   `Mood`. Empty unless there is one.
+///
+/// Literal braces are written as `{{` and `}}`. Unknown placeholders are drawn
+/// as they are.
+///
+/// # Example
+///
+/// ```rust
+/// use ferris_says::*;
+///
+/// struct Ghost;
+///
+/// impl Character for Ghost {
+///     fn name(&self) -> &str {
+///         "ghost"
+///     }
+///
+///     fn template(&self) -> &str {
+///         concat!(
+///             "        {tail}\n",
+///             "         {tail}  .-.\n",
+///             "            ({left_eye} {right_eye})\n",
+///             "            | O \\\n",
+///             "            \\   \\\n",
+///             "             `~~~'\n",
+///         )
+///     }
+/// }
+///
+/// let cfg = FerrisConfig {
+///     speaker: Speaker::custom(Ghost),
+///     ..Default::default()
+/// };
+/// let mut out = Vec::new();
+/// perform(b"Boo!", 40, &mut out, &cfg).unwrap();
+/// ```
+///
+/// This will print out:
+///
+/// ```plain
+///  ______
+/// < Boo! >
+///  ------
+///         \
+///          \  .-.
+///             (o o)
+///             | O \
+///             \   \
+///              `~~~'
+/// ```
+pub trait Character {
+    /// The name this character goes by, e.g. when picked on the command line
+    fn name(&self) -> &str;
+
+    /// The art of the character including its placeholders
+    fn template(&self) -> &str;
+
+    /// What to draw in the `{tongue}` slot
+    fn tongue(&self) -> &str {
+        "  "
+    }
+
+    /// What to draw in the `{mouth}` slot
+    fn mouth(&self) -> &str {
+        "-"
+    }
+
+    /// Who drew the character, if known
+    fn author(&self) -> Option<&str> {
+        None
+    }
+
+    /// A short description of the character
+    fn description(&self) -> Option<&str> {
+        None
+    }
+
+    /// The colours the character likes to be drawn in, none by default
+    fn palette(&self) -> Palette {
+        Palette::default()
+    }
+}
+
+impl Speaker {
+    /// Ferris and the friends that ship with this crate
+    pub fn builtin() -> [Speaker; 3] {
+        [Speaker::Ferris, Speaker::Clippy, Speaker::Cow]
+    }
+
+    /// Let a character of your own do the talking
+    pub fn custom<C>(character: C) -> Speaker
+    where
+        C: Character + Send + Sync + 'static,
+    {
+        Speaker::Custom(Arc::new(character))
+    }
+}
+
+impl Speaker {
+    /// The mouth and the tongue that draw `mouth` on this speaker
+    pub(crate) fn expression(&self, mouth: Mouth) -> (&str, &str) {
+        // Ferris and Clippy stick it out of their mouth
+        let tongue = match (self, mouth) {
+            (Speaker::Ferris, _) | (Speaker::Clippy, _) => self.tongue(),
+            (_, Mouth::TongueOut) => "U ",
+            (_, _) => self.tongue(),
+        };
+        // Clippy and the cow have two columns for their mouth, the cow's
+        // being its muzzle
+        let mouth = match (self, mouth) {
+            (_, Mouth::Neutral) => self.mouth(),
+            (Speaker::Clippy, Mouth::Smile) | (Speaker::Cow, Mouth::Smile) => "\\/",
+            (Speaker::Clippy, Mouth::Frown) | (Speaker::Cow, Mouth::Frown) => "/\\",
+            (Speaker::Clippy, Mouth::Open) | (Speaker::Cow, Mouth::Open) => "[]",
+            (Speaker::Clippy, Mouth::Surprised) | (Speaker::Cow, Mouth::Surprised) => "()",
+            (Speaker::Ferris, Mouth::TongueOut) => "P",
+            (Speaker::Clippy, Mouth::TongueOut) => " P",
+            (_, Mouth::TongueOut) => self.mouth(),
+            (_, Mouth::Smile) => "v",
+            (_, Mouth::Frown) => "^",
+            (_, Mouth::Open) => "O",
+            (_, Mouth::Surprised) => "o",
+        };
+        (mouth, tongue)
+    }
+}
+
+impl FromStr for Speaker {
+    type Err = Error;
+
+    /// Look up one of the built-in speakers by name
+    fn from_str(name: &str) -> Result<Speaker> {
+        Speaker::builtin()
+            .iter()
+            .find(|speaker| speaker.name() == name)
+            .cloned()
+            .ok_or_else(|| Error::UnknownSpeaker(name.to_owned()))
+    }
+}
+
+impl Character for Speaker {
+    fn name(&self) -> &str {
+        match self {
+            Speaker::Ferris => "ferris",
+            Speaker::Clippy => "clippy",
+            Speaker::Cow => "cow",
+            Speaker::Custom(character) => character.name(),
+        }
+    }
+
+    fn template(&self) -> &str {
+        match self {
+            Speaker::Ferris => FERRIS,
+            Speaker::Clippy => CLIPPY,
+            Speaker::Cow => COW,
+            Speaker::Custom(character) => character.template(),
+        }
+    }
+
+    fn tongue(&self) -> &str {
+        match self {
+            Speaker::Custom(character) => character.tongue(),
+            _ => "  ",
+        }
+    }
+
+    fn mouth(&self) -> &str {
+        match self {
+            Speaker::Ferris => "-",
+            Speaker::Clippy => "  ",
+            Speaker::Cow => "__",
+            Speaker::Custom(character) => character.mouth(),
+        }
+    }
+
+    fn author(&self) -> Option<&str> {
+        match self {
+            Speaker::Custom(character) => character.author(),
+            _ => None,
+        }
+    }
+
+    fn description(&self) -> Option<&str> {
+        match self {
+            Speaker::Ferris => Some("Ferris the Rustacean"),
+            Speaker::Clippy => Some("Clippy, always happy to lint"),
+            Speaker::Cow => Some("The classic cowsay cow"),
+            Speaker::Custom(character) => character.description(),
+        }
+    }
+
+    fn palette(&self) -> Palette {
+        match self {
+            Speaker::Ferris => Palette {
+                frame: Some(FERRIS_ORANGE),
+                text: None,
+                tail: Some(FERRIS_ORANGE),
+                speaker: Some(FERRIS_ORANGE),
+            },
+            // The yellow of the note Clippy pops up on and a paperclip
+            Speaker::Clippy => Palette {
+                frame: Some(Color::Rgb(255, 223, 96)),
+                text: None,
+                tail: Some(Color::Rgb(255, 223, 96)),
+                speaker: Some(Color::Rgb(176, 176, 176)),
+            },
+            // A white cow in front of a red barn
+            Speaker::Cow => Palette {
+                frame: Some(Color::Rgb(178, 34, 34)),
+                text: None,
+                tail: Some(Color::Rgb(178, 34, 34)),
+                speaker: Some(Color::BrightWhite),
+            },
+            Speaker::Custom(character) => character.palette(),
+        }
+    }
+}
+
+const FERRIS: &str = r#"        {tail}
+         {tail}
+            _~^~^~_{accessory}
+        \) /  {left_eye} {right_eye}  \ (/
+          '_   {mouth}{tongue} _'
+          / '-----' \
+"#;
+
+const COW: &str = r#"        {tail}
+         {tail}
+            ^__^
+            ({left_eye}{right_eye})\_______
+            ({mouth})\       )\/\
+             {tongue} ||----w |
+                ||     ||
+"#;
+
+const CLIPPY: &str = r#"        {tail}
+         {tail}
+            __{accessory}
+           /  \
+           |  |
+           {left_eye}  {right_eye}
+           |{mouth}|
+           || |/
+           || ||
+           |\_/|
+           \___/
+"#;
+
+/// What goes into the face of a character
+pub(crate) struct Face<'a> {
+    /// The left and the right eye
+    pub eyes: (&'a str, &'a str),
+    pub mouth: &'a str,
+    pub tongue: &'a str,
+    pub accessory: &'a str,
+}
+
+/// Draws `character` with its placeholders filled in, turned to face the
+/// other way if `mirror` is set
+///
+/// Templates leave a column for each of the `eyes`. Wider eyes take up the
+/// spaces next to them, as far as there are more than one, so the rest of
+/// the line stays where it was.
+pub(crate) fn draw<W>(
+    out: &mut W,
+    character: &dyn Character,
+    tail: &[&str],
+    face: &Face,
+    palette: &Palette,
+    mirror: bool,
+) -> fmt::Result
+where
+    W: fmt::Write,
+{
+    // The art line by line, in pieces of one colour each
+    let mut lines: Vec<Line> = vec![Vec::new()];
+    // Every `{tail}` takes the next piece of the tail
+    let mut tail = tail.iter().cycle();
+    let eyes = face.eyes;
+    // The columns the right eye took from the spaces after it
+    let mut right_excess = 0;
+    for token in tokens(character.template()) {
+        let (color, text) = match token {
+            Token::Text(text) => {
+                let spaces = text.len() - text.trim_start_matches(' ').len();
+                let taken = right_excess.min(spaces.saturating_sub(1));
+                right_excess = 0;
+                (palette.speaker, &text[taken..])
+            }
+            Token::Placeholder("tail") => (palette.tail, *tail.next().unwrap()),
+            Token::Placeholder("left_eye") => {
+                let line = lines.last_mut().unwrap();
+                if let Some((_, before)) = line.last_mut() {
+                    let spaces = before.len() - before.trim_end_matches(' ').len();
+                    let taken = excess(eyes.0).min(spaces.saturating_sub(1));
+                    if taken > 0 {
+                        let len = before.len() - taken;
+                        before.to_mut().truncate(len);
+                    }
+                }
+                line.push((palette.speaker, eye(eyes.0)));
+                continue;
+            }
+            Token::Placeholder("right_eye") => {
+                right_excess = excess(eyes.1);
+                lines.last_mut().unwrap().push((palette.speaker, eye(eyes.1)));
+                continue;
+            }
+            Token::Placeholder("tongue") => (palette.speaker, face.tongue),
+            Token::Placeholder("mouth") => (palette.speaker, face.mouth),
+            Token::Placeholder("accessory") => (palette.speaker, face.accessory),
+            Token::Placeholder(other) => {
+                let text = format!("{{{}}}", other);
+                lines.last_mut().unwrap().push((palette.speaker, Cow::Owned(text)));
+                continue;
+            }
+        };
+        for (i, text) in text.split('\n').enumerate() {
+            if i > 0 {
+                lines.push(Vec::new());
+            }
+            lines.last_mut().unwrap().push((color, Cow::Borrowed(text)));
+        }
+    }
+
+    if mirror {
+        mirror_lines(&mut lines);
+    }
+    for (i, line) in lines.iter().enumerate() {
+        if i > 0 {
+            out.write_char('\n')?;
+        }
+        for (color, text) in line {
+            color::paint(out, *color, text)?;
+        }
+    }
+    Ok(())
+}
+
+type Line<'a> = Vec<(Option<Color>, Cow<'a, str>)>;
+
+// The columns an eye takes up beyond the one it has
+fn excess(eye: &str) -> usize {
+    display_width(eye).saturating_sub(1)
+}
+
+// An eye filled up to its column if it is narrower
+fn eye(eye: &str) -> Cow<'_, str> {
+    if display_width(eye) == 0 {
+        Cow::Owned(format!("{} ", eye))
+    } else {
+        Cow::Borrowed(eye)
+    }
+}
+
+// Flip the art around, so it reads from right to left as a whole. Lines are
+// padded to the widest first so the art keeps its shape.
+fn mirror_lines(lines: &mut [Line<'_>]) {
+    let line_width = |line: &Line| line.iter().map(|(_, text)| display_width(text)).sum::<usize>();
+    let width = lines.iter().map(line_width).max().unwrap_or(0);
+
+    for line in lines.iter_mut() {
+        let padding = width - line_width(line);
+        if padding == width {
+            continue;
+        }
+        line.push((None, Cow::Owned(" ".repeat(padding))));
+        line.reverse();
+        for (_, text) in line.iter_mut() {
+            let mirrored: Vec<&str> = clusters(text).map(|(cluster, _)| mirror(cluster)).collect();
+            *text = Cow::Owned(mirrored.into_iter().rev().collect());
+        }
+        // What used to be the indentation
+        while line.last().is_some_and(|(_, text)| text.trim().is_empty()) {
+            line.pop();
+        }
+        if let Some((_, text)) = line.last_mut() {
+            *text = Cow::Owned(text.trim_end().to_owned());
+        }
+    }
+}
+
+// The character facing the other way, for the few that have one
+fn mirror(cluster: &str) -> &str {
+    const PAIRS: &[(&str, &str)] = &[("(", ")"), ("[", "]"), ("{", "}"), ("<", ">"), ("/", "\\")];
+    for &(left, right) in PAIRS {
+        if cluster == left {
+            return right;
+        } else if cluster == right {
+            return left;
+        }
+    }
+    cluster
+}
+
+/// The placeholders a template can use
+pub(crate) const PLACEHOLDERS: &[&str] = &[
+    "tail",
+    "left_eye",
+    "right_eye",
+    "tongue",
+    "mouth",
+    "accessory",
+];
+
+/// A piece of a template
+pub(crate) enum Token<'a> {
+    Text(&'a str),
+    Placeholder(&'a str),
+}
+
+/// Splits a template into text and placeholders, resolving `{{` and `}}`
+pub(crate) fn tokens(template: &str) -> Tokens<'_> {
+    Tokens { rest: template }
+}
+
+pub(crate) struct Tokens<'a> {
+    rest: &'a str,
+}
+
+impl<'a> Iterator for Tokens<'a> {
+    type Item = Token<'a>;
+
+    fn next(&mut self) -> Option<Token<'a>> {
+        let rest = self.rest;
+        if rest.is_empty() {
+            return None;
+        }
+
+        let (token, len) = match rest.find(['{', '}']) {
+            None => (Token::Text(rest), rest.len()),
+            Some(0) if rest.starts_with("{{") || rest.starts_with("}}") => {
+                (Token::Text(&rest[..1]), 2)
+            }
+            Some(0) => match placeholder(rest) {
+                Some(name) => (Token::Placeholder(name), name.len() + 2),
+                None => (Token::Text(&rest[..1]), 1),
+            },
+            Some(start) => (Token::Text(&rest[..start]), start),
+        };
+
+        self.rest = &rest[len..];
+        Some(token)
+    }
+}
+
+// The name of a `{placeholder}` at the very start of `text`
+fn placeholder(text: &str) -> Option<&str> {
+    let text = text.strip_prefix('{')?;
+    let len = text
+        .find(|c: char| !(c.is_alphanumeric() || c == '_'))
+        .unwrap_or(text.len());
+
+    if len > 0 && text[len..].starts_with('}') {
+        Some(&text[..len])
+    } else {
+        None
+    }
+}
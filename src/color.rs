@@ -0,0 +1,183 @@
+use std::fmt;
+use std::str::FromStr;
+
+use super::ansi::RESET;
+use super::{Error, Result};
+
+/// A colour the terminal is asked to draw in
+///
+/// The first sixteen are the basic colours of the terminal, which look the
+/// way the terminal theme wants them to.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Color {
+    Black,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+    BrightBlack,
+    BrightRed,
+    BrightGreen,
+    BrightYellow,
+    BrightBlue,
+    BrightMagenta,
+    BrightCyan,
+    BrightWhite,
+    /// One of the 256 colours of xterm
+    Ansi256(u8),
+    /// Red, green and blue
+    Rgb(u8, u8, u8),
+}
+
+/// The orange of Ferris
+pub const FERRIS_ORANGE: Color = Color::Rgb(247, 76, 0);
+
+impl Color {
+    /// The SGR parameters that select this colour for the foreground
+    pub(crate) fn sgr(&self) -> String {
+        match *self {
+            Color::Ansi256(index) => format!("38;5;{}", index),
+            Color::Rgb(r, g, b) => format!("38;2;{};{};{}", r, g, b),
+            basic => {
+                let index = basic.basic_index().unwrap();
+                if index < 8 {
+                    (30 + index).to_string()
+                } else {
+                    (90 + index - 8).to_string()
+                }
+            }
+        }
+    }
+
+    // The position among the sixteen basic colours
+    pub(crate) fn basic_index(&self) -> Option<u8> {
+        let index = match self {
+            Color::Black => 0,
+            Color::Red => 1,
+            Color::Green => 2,
+            Color::Yellow => 3,
+            Color::Blue => 4,
+            Color::Magenta => 5,
+            Color::Cyan => 6,
+            Color::White => 7,
+            Color::BrightBlack => 8,
+            Color::BrightRed => 9,
+            Color::BrightGreen => 10,
+            Color::BrightYellow => 11,
+            Color::BrightBlue => 12,
+            Color::BrightMagenta => 13,
+            Color::BrightCyan => 14,
+            Color::BrightWhite => 15,
+            Color::Ansi256(_) | Color::Rgb(..) => return None,
+        };
+        Some(index)
+    }
+}
+
+impl FromStr for Color {
+    type Err = Error;
+
+    /// Read a colour from one of the names of the basic colours such as
+    /// `bright-red`, the index of one of the 256 colours such as `208` or
+    /// red, green and blue in hex such as `#f74c00`
+    fn from_str(name: &str) -> Result<Color> {
+        let basic = [
+            "black", "red", "green", "yellow", "blue", "magenta", "cyan", "white",
+        ];
+        let unknown = || Error::UnknownColor(name.to_owned());
+
+        if let Some(hex) = name.strip_prefix('#') {
+            if hex.len() != 6 || !hex.is_ascii() {
+                return Err(unknown());
+            }
+            let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).map_err(|_| unknown());
+            return Ok(Color::Rgb(channel(0)?, channel(2)?, channel(4)?));
+        }
+        if let Ok(index) = name.parse() {
+            return Ok(Color::Ansi256(index));
+        }
+        let (bright, base) = match name.strip_prefix("bright-") {
+            Some(base) => (8, base),
+            None => (0, name),
+        };
+        basic
+            .iter()
+            .position(|&color| color == base)
+            .map(|index| BASIC[index + bright])
+            .ok_or_else(unknown)
+    }
+}
+
+pub(crate) const BASIC: [Color; 16] = [
+    Color::Black,
+    Color::Red,
+    Color::Green,
+    Color::Yellow,
+    Color::Blue,
+    Color::Magenta,
+    Color::Cyan,
+    Color::White,
+    Color::BrightBlack,
+    Color::BrightRed,
+    Color::BrightGreen,
+    Color::BrightYellow,
+    Color::BrightBlue,
+    Color::BrightMagenta,
+    Color::BrightCyan,
+    Color::BrightWhite,
+];
+
+/// The colours of the parts of the picture, `None` leaves a part alone
+///
+/// The default palette has no colours at all, every `Character` brings a
+/// palette of its own.
+///
+/// # Example
+///
+/// ```rust
+/// use ferris_says::*;
+///
+/// let cfg = FerrisConfig {
+///     palette: Some(Speaker::Ferris.palette()),
+///     ..Default::default()
+/// };
+/// let rendered = render(b"Hello", 40, &cfg).unwrap();
+/// assert!(rendered.text().contains("\x1b[38;2;247;76;0m"));
+/// // Colours don't take up room
+/// let plain = render(b"Hello", 40, &FerrisConfig::default()).unwrap();
+/// assert_eq!(rendered.width(), plain.width());
+/// ```
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Palette {
+    /// The frame of the bubble
+    pub frame: Option<Color>,
+    /// The text in the bubble
+    pub text: Option<Color>,
+    /// The tail leading from the bubble to the speaker
+    pub tail: Option<Color>,
+    /// The art of the speaker
+    pub speaker: Option<Color>,
+}
+
+/// Write `text` in `color`, line by line so every line can stand on its own
+pub(crate) fn paint<W: fmt::Write>(out: &mut W, color: Option<Color>, text: &str) -> fmt::Result {
+    let color = match color {
+        Some(color) => color,
+        None => return out.write_str(text),
+    };
+
+    for (i, line) in text.split('\n').enumerate() {
+        if i > 0 {
+            out.write_char('\n')?;
+        }
+        if line.trim().is_empty() {
+            out.write_str(line)?;
+        } else {
+            write!(out, "\x1b[{}m{}{}", color.sgr(), line, RESET)?;
+        }
+    }
+    Ok(())
+}
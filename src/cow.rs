@@ -0,0 +1,257 @@
+use std::error;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use super::speaker_file::file_stem;
+use super::{Character, Error};
+
+/// A speaker loaded from a classic cowsay `.cow` file
+///
+/// Only the part of Perl that `.cow` files actually use is understood: comment
+/// lines and a single `$the_cow` heredoc using the `$thoughts`, `$eyes` and
+/// `$tongue` placeholders. Anything else is refused with a `CowError`.
+///
+/// # Example
+///
+/// ```rust
+/// use ferris_says::*;
+///
+/// let source = r#"
+/// $the_cow = <<"EOC";
+///         $thoughts   ^__^
+///          $thoughts  ($eyes)\\_______
+///             (__)\\       )\\/\\
+///              $tongue ||----w |
+///                 ||     ||
+/// EOC
+/// "#;
+///
+/// let cow = CowFile::parse("default", source).unwrap();
+/// let cfg = FerrisConfig {
+///     speaker: Speaker::custom(cow),
+///     ..Default::default()
+/// };
+/// let mut out = Vec::new();
+/// perform(b"Moo", 40, &mut out, &cfg).unwrap();
+/// ```
+///
+/// This will print out:
+///
+/// ```plain
+///  _____
+/// < Moo >
+///  -----
+///         \   ^__^
+///          \  (oo)\_______
+///             (__)\       )\/\
+///                 ||----w |
+///                 ||     ||
+/// ```
+pub struct CowFile {
+    name: String,
+    template: String,
+}
+
+/// The reasons a `.cow` file can't be turned into a speaker
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CowError {
+    /// There is no `$the_cow` heredoc in the file
+    MissingCow,
+    /// The heredoc starting on `line` never reaches its `terminator`
+    UnterminatedHeredoc { line: usize, terminator: String },
+    /// A Perl statement other than the `$the_cow` assignment
+    UnsupportedStatement { line: usize, statement: String },
+    /// A variable other than `$thoughts`, `$eyes` and `$tongue` in the cow
+    UnsupportedVariable { line: usize, variable: String },
+}
+
+impl fmt::Display for CowError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CowError::MissingCow => write!(f, "no `$the_cow = <<EOC;` heredoc found"),
+            CowError::UnterminatedHeredoc { line, terminator } => write!(
+                f,
+                "line {}: heredoc is never terminated by `{}`",
+                line, terminator
+            ),
+            CowError::UnsupportedStatement { line, statement } => write!(
+                f,
+                "line {}: unsupported Perl statement `{}`",
+                line, statement
+            ),
+            CowError::UnsupportedVariable { line, variable } => write!(
+                f,
+                "line {}: unsupported variable `{}`, only $thoughts, $eyes and $tongue are known",
+                line, variable
+            ),
+        }
+    }
+}
+
+impl error::Error for CowError {}
+
+impl CowFile {
+    /// Parse the contents of a `.cow` file, `name` is what the speaker goes by
+    pub fn parse(name: &str, source: &str) -> Result<CowFile, CowError> {
+        let mut lines = source.lines().enumerate().map(|(i, line)| (i + 1, line));
+        let mut template = None;
+
+        while let Some((number, line)) = lines.next() {
+            let statement = line.trim();
+            if statement.is_empty() || statement.starts_with('#') {
+                continue;
+            }
+
+            let heredoc = match heredoc_start(statement) {
+                Some(heredoc) if template.is_none() => heredoc,
+                _ => {
+                    return Err(CowError::UnsupportedStatement {
+                        line: number,
+                        statement: statement.to_owned(),
+                    })
+                }
+            };
+
+            let mut cow = String::new();
+            let mut terminated = false;
+            for (body_number, body) in lines.by_ref() {
+                if body == heredoc.terminator {
+                    terminated = true;
+                    break;
+                }
+                if heredoc.interpolate {
+                    interpolate(body, body_number, &mut cow)?;
+                } else {
+                    escape_braces(body, &mut cow);
+                }
+                cow.push('\n');
+            }
+
+            if !terminated {
+                return Err(CowError::UnterminatedHeredoc {
+                    line: number,
+                    terminator: heredoc.terminator.to_owned(),
+                });
+            }
+            template = Some(cow);
+        }
+
+        Ok(CowFile {
+            name: name.to_owned(),
+            template: template.ok_or(CowError::MissingCow)?,
+        })
+    }
+
+    /// Load a `.cow` file from disk, the speaker is named after the file
+    ///
+    /// Files that can't be parsed result in an `Error::Cow` wrapping the
+    /// `CowError`.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<CowFile, Error> {
+        let path = path.as_ref();
+        let source = fs::read_to_string(path)?;
+
+        CowFile::parse(&file_stem(path), &source).map_err(|source| Error::Cow {
+            path: path.to_owned(),
+            source,
+        })
+    }
+}
+
+impl Character for CowFile {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn template(&self) -> &str {
+        &self.template
+    }
+}
+
+struct Heredoc<'a> {
+    terminator: &'a str,
+    interpolate: bool,
+}
+
+// Recognises `$the_cow = <<"EOC";`, `<<EOC;` and `<<'EOC';`
+fn heredoc_start(statement: &str) -> Option<Heredoc<'_>> {
+    let rest = statement.strip_prefix("$the_cow")?.trim_start();
+    let rest = rest.strip_prefix('=')?.trim_start();
+    let rest = rest.strip_prefix("<<")?;
+    let rest = rest.strip_suffix(';')?.trim_end();
+
+    let (terminator, interpolate) = if let Some(quoted) = rest.strip_prefix('"') {
+        (quoted.strip_suffix('"')?, true)
+    } else if let Some(quoted) = rest.strip_prefix('\'') {
+        (quoted.strip_suffix('\'')?, false)
+    } else {
+        (rest, true)
+    };
+
+    if terminator.is_empty() || !terminator.chars().all(|c| c.is_alphanumeric() || c == '_') {
+        return None;
+    }
+    Some(Heredoc { terminator, interpolate })
+}
+
+// Turns a line of a double quoted heredoc into template syntax
+fn interpolate(line: &str, number: usize, cow: &mut String) -> Result<(), CowError> {
+    let mut chars = line.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some('e') => cow.push('\x1b'),
+                Some('t') => cow.push('\t'),
+                Some(escaped) => escape_braces(escaped.encode_utf8(&mut [0; 4]), cow),
+                None => cow.push('\\'),
+            },
+            '$' | '@' => {
+                let braced = chars.peek() == Some(&'{');
+                if braced {
+                    chars.next();
+                }
+                let mut name = String::new();
+                while let Some(&next) = chars.peek() {
+                    if !(next.is_alphanumeric() || next == '_') {
+                        break;
+                    }
+                    name.push(next);
+                    chars.next();
+                }
+                if braced && chars.next() != Some('}') {
+                    return Err(CowError::UnsupportedVariable {
+                        line: number,
+                        variable: format!("{}{{{}", c, name),
+                    });
+                }
+
+                match (c, name.as_str()) {
+                    (_, "") if !braced => cow.push(c),
+                    ('$', "thoughts") => cow.push_str("{tail}"),
+                    ('$', "eyes") => cow.push_str("{left_eye}{right_eye}"),
+                    ('$', "tongue") => cow.push_str("{tongue}"),
+                    _ => {
+                        return Err(CowError::UnsupportedVariable {
+                            line: number,
+                            variable: format!("{}{}", c, name),
+                        })
+                    }
+                }
+            }
+            _ => escape_braces(c.encode_utf8(&mut [0; 4]), cow),
+        }
+    }
+
+    Ok(())
+}
+
+fn escape_braces(text: &str, cow: &mut String) {
+    for c in text.chars() {
+        match c {
+            '{' => cow.push_str("{{"),
+            '}' => cow.push_str("}}"),
+            _ => cow.push(c),
+        }
+    }
+}
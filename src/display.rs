@@ -0,0 +1,190 @@
+use std::fmt;
+
+use super::{
+    draw, BubbleStyle, ColorSupport, Controls, Effect, Eyes, FerrisConfig, Mood, Mouth, Palette,
+    Speaker, SpeechModes, Wrapping,
+};
+
+/// The width used by `Ferris` unless told otherwise, the same as `fsays` uses
+pub const DEFAULT_WIDTH: usize = 40;
+
+/// Ferris saying or thinking something, drawn when formatted with `Display`
+///
+/// Nothing is rendered until the value is formatted, so it can go straight
+/// into `println!`, `format!` or `write!` as well as into the `Display` and
+/// `Debug` implementations of your own types. Anything that implements
+/// `Display` can be said, see `ferris_say!` for formatted text.
+///
+/// # Example
+///
+/// ```rust
+/// use ferris_says::*;
+///
+/// println!("{}", Ferris::says("build ok"));
+///
+/// let clippy = Ferris::thinks(42).width(20).speaker(Speaker::Clippy);
+/// let text = format!("{}", clippy);
+/// assert_eq!(text.lines().nth(1), Some("( 42 )"));
+/// ```
+pub struct Ferris<T> {
+    text: T,
+    width: usize,
+    cfg: FerrisConfig,
+}
+
+impl<T: fmt::Display> Ferris<T> {
+    /// Let Ferris say `text`
+    pub fn says(text: T) -> Ferris<T> {
+        Ferris {
+            text,
+            width: DEFAULT_WIDTH,
+            cfg: FerrisConfig::default(),
+        }
+    }
+
+    /// Let Ferris think `text`
+    pub fn thinks(text: T) -> Ferris<T> {
+        Ferris::says(text).mode(SpeechModes::Think)
+    }
+
+    /// The maximum width of a line of text before it is wrapped
+    ///
+    /// As `Display` can't report `Error::InvalidWidth`, a width of 0 is
+    /// treated like a width of 1.
+    pub fn width(mut self, width: usize) -> Ferris<T> {
+        self.width = width;
+        self
+    }
+
+    /// Whether to say or think the text
+    pub fn mode(mut self, mode: SpeechModes) -> Ferris<T> {
+        self.cfg.mode = mode;
+        self
+    }
+
+    /// Ferris has different moods
+    pub fn eyes(mut self, eyes: Eyes) -> Ferris<T> {
+        self.cfg.eyes = eyes;
+        self
+    }
+
+    /// Smile, frown or stick the tongue out
+    pub fn mouth(mut self, mouth: Mouth) -> Ferris<T> {
+        self.cfg.mouth = mouth;
+        self
+    }
+
+    /// Set the eyes, the mouth and what goes with them the way cowsay does,
+    /// see `Mood`
+    pub fn mood(mut self, mood: Mood) -> Ferris<T> {
+        self.cfg.mood = Some(mood);
+        self
+    }
+
+    /// A tongue of your own, such as `"U "`
+    pub fn tongue<S: Into<String>>(mut self, tongue: S) -> Ferris<T> {
+        self.cfg.tongue = Some(tongue.into());
+        self
+    }
+
+    /// Let one of Ferris' friends talk instead
+    pub fn speaker(mut self, speaker: Speaker) -> Ferris<T> {
+        self.cfg.speaker = speaker;
+        self
+    }
+
+    /// The look of the speech bubble
+    pub fn bubble(mut self, bubble: BubbleStyle) -> Ferris<T> {
+        self.cfg.bubble = bubble;
+        self
+    }
+
+    /// Turn the text into capitals
+    pub fn uppercase(mut self, uppercase: bool) -> Ferris<T> {
+        self.cfg.uppercase = uppercase;
+        self
+    }
+
+    /// Paint the picture in these colours, see `Character::palette` for the
+    /// colours of the speaker
+    pub fn palette(mut self, palette: Palette) -> Ferris<T> {
+        self.cfg.palette = Some(palette);
+        self
+    }
+
+    /// The colours the terminal can show, see `ColorSupport::detect`
+    pub fn color_support(mut self, color_support: ColorSupport) -> Ferris<T> {
+        self.cfg.color_support = color_support;
+        self
+    }
+
+    /// Paint the text with a rainbow or a gradient
+    pub fn effect(mut self, effect: Effect) -> Ferris<T> {
+        self.cfg.effect = Some(effect);
+        self
+    }
+
+    /// Keep the colours of the text, see `FerrisConfig::keep_colors`
+    pub fn keep_colors(mut self, keep_colors: bool) -> Ferris<T> {
+        self.cfg.keep_colors = keep_colors;
+        self
+    }
+
+    /// What happens to control characters in the text, see `Controls`
+    pub fn controls(mut self, controls: Controls) -> Ferris<T> {
+        self.cfg.controls = controls;
+        self
+    }
+
+    /// Turn the picture around so the speaker stands on the right
+    pub fn mirror(mut self, mirror: bool) -> Ferris<T> {
+        self.cfg.mirror = mirror;
+        self
+    }
+
+    /// How the text is broken into lines
+    pub fn wrapping(mut self, wrapping: Wrapping) -> Ferris<T> {
+        self.cfg.wrapping = wrapping;
+        self
+    }
+}
+
+impl<T: fmt::Display> fmt::Display for Ferris<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // The whole text is needed up front to wrap it
+        let text = self.text.to_string();
+        draw(&text, self.width.max(1), &self.cfg, f)
+    }
+}
+
+/// Let Ferris say a formatted message
+///
+/// Takes the same arguments as `format!`, optionally preceded by settings of
+/// `Ferris` in the form of `name = value,` such as `width`, `mode`, `eyes` and
+/// `speaker`. The message is formatted right away, the result is a
+/// `Ferris<String>` which is drawn once it's formatted itself, so it can be
+/// kept around for later.
+///
+/// # Example
+///
+/// ```rust
+/// #[macro_use]
+/// extern crate ferris_says;
+///
+/// use ferris_says::*;
+///
+/// # fn main() {
+/// let n = 3;
+/// println!("{}", ferris_say!("built {} crates", n));
+/// println!("{}", ferris_say!(width = 12, eyes = Eyes::HappyEyes, "built {} crates", n));
+///
+/// let done = ferris_say!(mode = SpeechModes::Think, "built {} crates", n);
+/// assert_eq!(done.to_string().lines().nth(1), Some("( built 3 crates )"));
+/// # }
+/// ```
+#[macro_export]
+macro_rules! ferris_say {
+    ($($setting:ident = $value:expr,)* $fmt:literal $($args:tt)*) => {
+        $crate::Ferris::says(format!($fmt $($args)*)) $(.$setting($value))*
+    };
+}
@@ -0,0 +1,167 @@
+use std::fmt;
+
+use super::ansi::RESET;
+use super::width::{clusters, display_width};
+use super::{Color, ColorSupport};
+
+/// A lolcat style colour effect for the text in the bubble
+///
+/// Every column gets a colour of its own, so the effect needs a terminal
+/// that can show more than the basic colours to look its best. Effects are
+/// deterministic, the same text, effect and seed always come out the same.
+///
+/// # Example
+///
+/// ```rust
+/// use ferris_says::*;
+///
+/// let cfg = FerrisConfig {
+///     effect: Some(Effect {
+///         colors: EffectColors::Rainbow { seed: 7 },
+///         direction: Direction::Diagonal,
+///         speaker: true,
+///     }),
+///     ..Default::default()
+/// };
+/// let rendered = render(b"Hello fellow Rustaceans!", 12, &cfg).unwrap();
+/// assert_eq!(rendered, render(b"Hello fellow Rustaceans!", 12, &cfg).unwrap());
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Effect {
+    /// Where the colours come from
+    pub colors: EffectColors,
+    /// Which way the colours change
+    pub direction: Direction,
+    /// Paint the speaker and the tail with the effect as well
+    pub speaker: bool,
+}
+
+/// Where the colours of an `Effect` come from
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum EffectColors {
+    /// All colours of the rainbow over and over, `seed` picks the colour to
+    /// start with
+    Rainbow { seed: u64 },
+    /// From one colour to another over the width of the text
+    Gradient { from: Color, to: Color },
+}
+
+/// Which way the colours of an `Effect` change
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Direction {
+    /// From left to right
+    #[default]
+    Horizontal,
+    /// From the top left to the bottom right
+    Diagonal,
+}
+
+// How far the hue of the rainbow moves from one column to the next, in
+// degrees
+const RAINBOW_STEP: u64 = 12;
+
+/// An effect ready to be painted for a terminal with `support`
+pub(crate) struct Brush {
+    pub(crate) effect: Effect,
+    pub(crate) support: ColorSupport,
+}
+
+impl Brush {
+    /// Paint all lines of `text`
+    pub(crate) fn paint<W: fmt::Write>(&self, out: &mut W, text: &str) -> fmt::Result {
+        let lines: Vec<&str> = text.split('\n').collect();
+        let width = lines.iter().map(|line| display_width(line)).max().unwrap_or(0);
+        for (row, line) in lines.iter().enumerate() {
+            if row > 0 {
+                out.write_char('\n')?;
+            }
+            self.paint_line(out, line, row, (width, lines.len()))?;
+        }
+        Ok(())
+    }
+
+    /// Paint `line`, the line at `row` of a block of `size` columns and rows
+    pub(crate) fn paint_line<W>(&self, out: &mut W, line: &str, row: usize, size: (usize, usize)) -> fmt::Result
+    where
+        W: fmt::Write,
+    {
+        let mut current = None;
+        // Colours in the text itself may have replaced the current one
+        let mut overridden = false;
+        let mut column = 0;
+        for (cluster, width) in clusters(line) {
+            if cluster.starts_with(char::is_control) {
+                out.write_str(cluster)?;
+                overridden = true;
+                continue;
+            }
+
+            // Marks and other clusters without a width stay with the cluster
+            // before them
+            if width > 0 {
+                let color = if cluster.trim().is_empty() {
+                    None
+                } else {
+                    self.color_at(column, row, size)
+                };
+                if color != current || overridden {
+                    match color {
+                        Some(color) => write!(out, "\x1b[{}m", color.sgr())?,
+                        None => out.write_str(RESET)?,
+                    }
+                    current = color;
+                    overridden = false;
+                }
+            }
+            out.write_str(cluster)?;
+            column += width;
+        }
+        if current.is_some() || overridden {
+            out.write_str(RESET)?;
+        }
+        Ok(())
+    }
+
+    fn color_at(&self, column: usize, row: usize, (width, height): (usize, usize)) -> Option<Color> {
+        let (position, length) = match self.effect.direction {
+            Direction::Horizontal => (column, width),
+            Direction::Diagonal => (column + row, width + height.saturating_sub(1)),
+        };
+
+        let color = match self.effect.colors {
+            EffectColors::Rainbow { seed } => {
+                // Spread the seeds out, so neighbouring seeds look different
+                let start = seed.wrapping_mul(0x9e37_79b9_7f4a_7c15) >> 32;
+                hue(start.wrapping_add(position as u64 * RAINBOW_STEP) % 360)
+            }
+            EffectColors::Gradient { from, to } => {
+                let end = length.saturating_sub(1).max(1);
+                mix(from.rgb(), to.rgb(), position.min(end), end)
+            }
+        };
+        color.downsample(self.support)
+    }
+}
+
+// The colour of the rainbow at `hue` degrees, in full saturation
+fn hue(hue: u64) -> Color {
+    let rising = (hue % 60 * 255 / 60) as u8;
+    let falling = 255 - rising;
+    match hue / 60 {
+        0 => Color::Rgb(255, rising, 0),
+        1 => Color::Rgb(falling, 255, 0),
+        2 => Color::Rgb(0, 255, rising),
+        3 => Color::Rgb(0, falling, 255),
+        4 => Color::Rgb(rising, 0, 255),
+        _ => Color::Rgb(255, 0, falling),
+    }
+}
+
+// `step` steps of `steps` on the way from `from` to `to`
+fn mix(from: (u8, u8, u8), to: (u8, u8, u8), step: usize, steps: usize) -> Color {
+    let channel = |from: u8, to: u8| {
+        let (from, to) = (from as usize, to as usize);
+        ((from * (steps - step) + to * step) / steps) as u8
+    };
+    Color::Rgb(channel(from.0, to.0), channel(from.1, to.1), channel(from.2, to.2))
+}
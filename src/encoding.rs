@@ -0,0 +1,133 @@
+use std::borrow::Cow;
+use std::char;
+use std::str::{self, FromStr};
+
+use super::{Error, Result};
+
+/// How the bytes handed to `perform` are turned into text
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Encoding {
+    /// UTF-8, anything else is an error, the bytes are taken as they are
+    #[default]
+    Utf8,
+    /// UTF-8, invalid sequences are replaced with `U+FFFD`
+    Utf8Lossy,
+    /// UTF-16 in the byte order given by its byte order mark, little endian
+    /// if there is none
+    Utf16,
+    /// UTF-16, little endian
+    Utf16Le,
+    /// UTF-16, big endian
+    Utf16Be,
+    /// ISO 8859-1, every byte is a character
+    Latin1,
+}
+
+const UTF8_BOM: &[u8] = b"\xef\xbb\xbf";
+const UTF16LE_BOM: &[u8] = b"\xff\xfe";
+const UTF16BE_BOM: &[u8] = b"\xfe\xff";
+
+impl Encoding {
+    /// Turn `input` into text, a leading byte order mark is dropped by all
+    /// but `Encoding::Utf8` and `Encoding::Latin1`
+    ///
+    /// Fails with `Error::InvalidUtf8` or `Error::InvalidUtf16` if `input`
+    /// can't be decoded strictly.
+    pub fn decode<'a>(&self, input: &'a [u8]) -> Result<Cow<'a, str>> {
+        match self {
+            Encoding::Utf8 => match str::from_utf8(input) {
+                Ok(text) => Ok(Cow::Borrowed(text)),
+                Err(e) => Err(Error::InvalidUtf8 {
+                    offset: e.valid_up_to(),
+                    source: e,
+                }),
+            },
+            Encoding::Utf8Lossy => Ok(String::from_utf8_lossy(strip(input, UTF8_BOM))),
+            Encoding::Utf16 if input.starts_with(UTF16BE_BOM) => Encoding::Utf16Be.decode(input),
+            Encoding::Utf16 | Encoding::Utf16Le => {
+                decode_utf16(strip(input, UTF16LE_BOM), u16::from_le_bytes, bom_len(input, UTF16LE_BOM))
+            }
+            Encoding::Utf16Be => {
+                decode_utf16(strip(input, UTF16BE_BOM), u16::from_be_bytes, bom_len(input, UTF16BE_BOM))
+            }
+            Encoding::Latin1 => Ok(Cow::Owned(input.iter().map(|&byte| char::from(byte)).collect())),
+        }
+    }
+
+    /// The name used to pick the encoding, e.g. on the command line
+    pub fn name(&self) -> &'static str {
+        match self {
+            Encoding::Utf8 => "utf8",
+            Encoding::Utf8Lossy => "utf8-lossy",
+            Encoding::Utf16 => "utf16",
+            Encoding::Utf16Le => "utf16le",
+            Encoding::Utf16Be => "utf16be",
+            Encoding::Latin1 => "latin1",
+        }
+    }
+
+    /// All the encodings there are
+    pub fn all() -> [Encoding; 6] {
+        [
+            Encoding::Utf8,
+            Encoding::Utf8Lossy,
+            Encoding::Utf16,
+            Encoding::Utf16Le,
+            Encoding::Utf16Be,
+            Encoding::Latin1,
+        ]
+    }
+}
+
+impl FromStr for Encoding {
+    type Err = Error;
+
+    /// Look up an encoding by its `name`
+    fn from_str(name: &str) -> Result<Encoding> {
+        Encoding::all()
+            .iter()
+            .find(|encoding| encoding.name() == name)
+            .cloned()
+            .ok_or_else(|| Error::UnknownEncoding(name.to_owned()))
+    }
+}
+
+fn strip<'a>(input: &'a [u8], bom: &[u8]) -> &'a [u8] {
+    &input[bom_len(input, bom)..]
+}
+
+fn bom_len(input: &[u8], bom: &[u8]) -> usize {
+    if input.starts_with(bom) {
+        bom.len()
+    } else {
+        0
+    }
+}
+
+// `offset` is where `input` starts in what was handed to `decode`, so errors
+// point at the right byte
+fn decode_utf16<'a, F>(input: &[u8], from_bytes: F, offset: usize) -> Result<Cow<'a, str>>
+where
+    F: Fn([u8; 2]) -> u16,
+{
+    if input.len() % 2 != 0 {
+        return Err(Error::InvalidUtf16 {
+            offset: offset + input.len() - 1,
+        });
+    }
+
+    let units = input.chunks(2).map(|pair| from_bytes([pair[0], pair[1]]));
+    let mut text = String::with_capacity(input.len() / 2);
+    let mut position = offset;
+    for decoded in char::decode_utf16(units) {
+        match decoded {
+            Ok(c) => {
+                text.push(c);
+                position += c.len_utf16() * 2;
+            }
+            Err(_) => return Err(Error::InvalidUtf16 { offset: position }),
+        }
+    }
+
+    Ok(Cow::Owned(text))
+}
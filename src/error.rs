@@ -0,0 +1,137 @@
+use std::error;
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+use std::result;
+use std::str::Utf8Error;
+
+use super::{CowError, SpeakerFileError};
+
+/// Everything that can go wrong while getting Ferris to talk
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// The input isn't valid UTF-8, `offset` is the index of the first
+    /// offending byte
+    InvalidUtf8 { offset: usize, source: Utf8Error },
+    /// The input isn't valid UTF-16, `offset` is the index of the first
+    /// offending byte
+    InvalidUtf16 { offset: usize },
+    /// A width text can't be wrapped to
+    InvalidWidth(usize),
+    /// No speaker goes by this name
+    UnknownSpeaker(String),
+    /// There are no eyes by this name
+    UnknownEyes(String),
+    /// There is no mouth by this name
+    UnknownMouth(String),
+    /// There is no mood by this name
+    UnknownMood(String),
+    /// There is no encoding by this name
+    UnknownEncoding(String),
+    /// There is no way to handle newlines by this name
+    UnknownLineBreaks(String),
+    /// There is no wrapping algorithm by this name
+    UnknownWrapAlgorithm(String),
+    /// There is no way to split words by this name
+    UnknownHyphenation(String),
+    /// There is no bubble style by this name
+    UnknownBubbleStyle(String),
+    /// There is no speech mode by this name
+    UnknownSpeechMode(String),
+    /// A colour that can't be read
+    UnknownColor(String),
+    /// There is no way to handle control characters by this name
+    UnknownControls(String),
+    /// The `.cow` file at `path` couldn't be parsed
+    Cow { path: PathBuf, source: CowError },
+    /// The speaker file at `path` couldn't be parsed
+    SpeakerFile { path: PathBuf, source: SpeakerFileError },
+    /// Reading or writing failed
+    Io(io::Error),
+}
+
+/// `Result` with `ferris_says::Error` as the error
+pub type Result<T> = result::Result<T, Error>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::InvalidUtf8 { offset, .. } => {
+                write!(f, "input is not valid UTF-8 at byte {}", offset)
+            }
+            Error::InvalidUtf16 { offset } => {
+                write!(f, "input is not valid UTF-16 at byte {}", offset)
+            }
+            Error::InvalidWidth(width) => write!(f, "can't wrap text to a width of {}", width),
+            Error::UnknownSpeaker(name) => write!(f, "unknown speaker `{}`", name),
+            Error::UnknownEyes(name) => write!(f, "unknown eyes `{}`", name),
+            Error::UnknownMouth(name) => write!(f, "unknown mouth `{}`", name),
+            Error::UnknownMood(name) => write!(f, "unknown mood `{}`", name),
+            Error::UnknownEncoding(name) => write!(f, "unknown encoding `{}`", name),
+            Error::UnknownLineBreaks(name) => write!(f, "unknown line breaks `{}`", name),
+            Error::UnknownWrapAlgorithm(name) => write!(f, "unknown wrapping algorithm `{}`", name),
+            Error::UnknownHyphenation(name) => write!(f, "unknown hyphenation `{}`", name),
+            Error::UnknownBubbleStyle(name) => write!(f, "unknown bubble style `{}`", name),
+            Error::UnknownSpeechMode(name) => write!(f, "unknown speech mode `{}`", name),
+            Error::UnknownColor(name) => write!(f, "unknown colour `{}`", name),
+            Error::UnknownControls(name) => write!(f, "unknown handling of controls `{}`", name),
+            Error::Cow { path, .. } => write!(f, "failed to parse the cow file {}", path.display()),
+            Error::SpeakerFile { path, .. } => {
+                write!(f, "failed to parse the speaker file {}", path.display())
+            }
+            Error::Io(_) => write!(f, "I/O error"),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::InvalidUtf8 { source, .. } => Some(source),
+            Error::Cow { source, .. } => Some(source),
+            Error::SpeakerFile { source, .. } => Some(source),
+            Error::Io(source) => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Error {
+        Error::Io(e)
+    }
+}
+
+impl From<Utf8Error> for Error {
+    fn from(e: Utf8Error) -> Error {
+        Error::InvalidUtf8 {
+            offset: e.valid_up_to(),
+            source: e,
+        }
+    }
+}
+
+// Keeps `?` working in functions returning `io::Result`, as all of this
+// crate used to
+impl From<Error> for io::Error {
+    fn from(e: Error) -> io::Error {
+        match e {
+            Error::Io(e) => e,
+            Error::InvalidWidth(_)
+            | Error::UnknownSpeaker(_)
+            | Error::UnknownEyes(_)
+            | Error::UnknownMouth(_)
+            | Error::UnknownMood(_)
+            | Error::UnknownEncoding(_)
+            | Error::UnknownLineBreaks(_)
+            | Error::UnknownWrapAlgorithm(_)
+            | Error::UnknownHyphenation(_)
+            | Error::UnknownBubbleStyle(_)
+            | Error::UnknownSpeechMode(_)
+            | Error::UnknownColor(_)
+            | Error::UnknownControls(_) => io::Error::new(io::ErrorKind::InvalidInput, e),
+            _ => io::Error::new(io::ErrorKind::InvalidData, e),
+        }
+    }
+}
@@ -1,36 +1,121 @@
-extern crate smallvec;
 extern crate textwrap;
+extern crate unicode_bidi;
+extern crate unicode_segmentation;
 extern crate unicode_width;
 extern crate lazy_static;
-
-use smallvec::*;
-use std::io::{Result, Write};
-use std::iter::repeat;
-use std::str;
-use std::sync::Mutex;
-use textwrap::fill;
-use unicode_width::UnicodeWidthStr;
+#[cfg(feature = "hyphenation")]
+extern crate hyphenation;
+
+mod ansi;
+mod bidi;
+mod bubble;
+mod capability;
+mod character;
+mod color;
+mod cow;
+mod display;
+mod effect;
+mod encoding;
+mod error;
+mod mood;
+mod rendered;
+mod speaker_file;
+mod width;
+mod wrap;
+
+pub use ansi::Controls;
+pub use bubble::{BubbleStyle, Frame};
+pub use capability::ColorSupport;
+pub use character::Character;
+pub use color::{Color, Palette, FERRIS_ORANGE};
+pub use cow::{CowError, CowFile};
+pub use display::{Ferris, DEFAULT_WIDTH};
+pub use effect::{Direction, Effect, EffectColors};
+pub use encoding::Encoding;
+pub use error::{Error, Result};
+pub use mood::Mood;
+pub use rendered::Rendered;
+pub use speaker_file::{SpeakerFile, SpeakerFileError};
+pub use wrap::{Hyphenation, LineBreaks, WrapAlgorithm, Wrapping};
+
+use std::borrow::Cow;
+use std::fmt;
+use std::io::Write;
+use std::str::{self, FromStr};
+use std::sync::{Arc, Mutex, PoisonError};
+use character::Face;
+use effect::Brush;
+use width::display_width;
 use lazy_static::lazy_static;
 
-#[derive(Copy, Clone)]
+#[derive(Clone, Default)]
 pub enum Speaker {
+    #[default]
     Ferris,
     Clippy,
-    Cow
+    Cow,
+    Custom(Arc<dyn Character + Send + Sync>)
 }
 
-
+// Only read by `say` and `think`, kept around so `set_speaker` still works
 lazy_static! {
     static ref SPEAKER: Mutex<Speaker> = Mutex::new(Speaker::Ferris);
 }
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum SpeechModes {
     Think,
     Say,
+    /// A spiky bubble, set `uppercase` of `FerrisConfig` to really shout
+    Shout,
+    /// A dotted bubble
+    Whisper,
+    /// Notes all around the bubble
+    Sing,
+    /// Closed eyes and a trail of `z`s up to the bubble
+    Sleep,
 }
 
-#[derive(Copy, Clone)]
+impl SpeechModes {
+    /// The name used to pick the mode, e.g. on the command line
+    pub fn name(&self) -> &'static str {
+        match self {
+            SpeechModes::Say => "say",
+            SpeechModes::Think => "think",
+            SpeechModes::Shout => "shout",
+            SpeechModes::Whisper => "whisper",
+            SpeechModes::Sing => "sing",
+            SpeechModes::Sleep => "sleep",
+        }
+    }
+
+    /// All the speech modes there are
+    pub fn all() -> [SpeechModes; 6] {
+        [
+            SpeechModes::Say,
+            SpeechModes::Think,
+            SpeechModes::Shout,
+            SpeechModes::Whisper,
+            SpeechModes::Sing,
+            SpeechModes::Sleep,
+        ]
+    }
+}
+
+impl FromStr for SpeechModes {
+    type Err = Error;
+
+    /// Look up a speech mode by its `name`
+    fn from_str(name: &str) -> Result<SpeechModes> {
+        SpeechModes::all()
+            .iter()
+            .find(|mode| mode.name() == name)
+            .cloned()
+            .ok_or_else(|| Error::UnknownSpeechMode(name.to_owned()))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Eyes {
     RegularEyes,
     GreedyEyes,
@@ -39,70 +124,239 @@ pub enum Eyes {
     DeadEyes,
     TiredEyes,
     CryingEyes,
-    HappyEyes
+    HappyEyes,
+    /// Eyes of your own, which can differ from each other for a wink and be
+    /// wider than a column. See `Eyes::custom`.
+    Custom { left: String, right: String }
+}
+
+impl Eyes {
+    /// Eyes of your own, e.g. `Eyes::custom("^", "o")` for a wink
+    ///
+    /// The art makes room for eyes wider than a column where it has spaces
+    /// next to them to spare.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use ferris_says::*;
+    ///
+    /// let cfg = FerrisConfig { eyes: Eyes::custom("◉", "◉"), ..Default::default() };
+    /// let rendered = render(b"Hi", 40, &cfg).unwrap();
+    /// assert_eq!(rendered.lines().nth(6), Some("        \\) /  ◉ ◉  \\ (/"));
+    /// ```
+    pub fn custom<L, R>(left: L, right: R) -> Eyes
+    where
+        L: Into<String>,
+        R: Into<String>,
+    {
+        Eyes::Custom {
+            left: left.into(),
+            right: right.into(),
+        }
+    }
+
+    /// The name used to pick the eyes, e.g. on the command line
+    pub fn name(&self) -> &'static str {
+        match self {
+            Eyes::RegularEyes => "regular",
+            Eyes::GreedyEyes => "greedy",
+            Eyes::YouthfulEyes => "youth",
+            Eyes::ParanoidEyes => "paranoid",
+            Eyes::DeadEyes => "dead",
+            Eyes::TiredEyes => "tired",
+            Eyes::CryingEyes => "crying",
+            Eyes::HappyEyes => "happy",
+            Eyes::Custom { .. } => "custom",
+        }
+    }
+
+    /// The eyes that ship with this crate
+    pub fn builtin() -> [Eyes; 8] {
+        [
+            Eyes::RegularEyes,
+            Eyes::GreedyEyes,
+            Eyes::YouthfulEyes,
+            Eyes::ParanoidEyes,
+            Eyes::DeadEyes,
+            Eyes::TiredEyes,
+            Eyes::CryingEyes,
+            Eyes::HappyEyes,
+        ]
+    }
+
+    /// The left and the right eye
+    pub fn glyphs(&self) -> (&str, &str) {
+        let eye = match self {
+            Eyes::CryingEyes => CRYING_EYES,
+            Eyes::DeadEyes => DEAD_EYES,
+            Eyes::RegularEyes => REGULAR_EYES,
+            Eyes::GreedyEyes => GREEDY_EYES,
+            Eyes::ParanoidEyes => PARANOID_EYES,
+            Eyes::YouthfulEyes => YOUTHFUL_EYES,
+            Eyes::TiredEyes => TIRED_EYES,
+            Eyes::HappyEyes => HAPPY_EYES,
+            Eyes::Custom { left, right } => return (left, right),
+        };
+        (eye, eye)
+    }
+}
+
+impl FromStr for Eyes {
+    type Err = Error;
+
+    /// Look up eyes by the names `fsays` knows them by, such as `happy`
+    fn from_str(name: &str) -> Result<Eyes> {
+        Eyes::builtin()
+            .iter()
+            .find(|eyes| eyes.name() == name)
+            .cloned()
+            .ok_or_else(|| Error::UnknownEyes(name.to_owned()))
+    }
+}
+
+/// What the speaker does with its mouth
+///
+/// Every built-in speaker has its own take on each of them. Custom speakers
+/// keep their own mouth with `Mouth::Neutral` and use simple ones otherwise.
+/// `TongueOut` fills the `{tongue}` slot of the art, as `cowsay -T "U "`
+/// does.
+///
+/// # Example
+///
+/// ```rust
+/// use ferris_says::*;
+///
+/// let cfg = FerrisConfig { mouth: Mouth::Smile, ..Default::default() };
+/// let rendered = render(b"Hi", 40, &cfg).unwrap();
+/// assert_eq!(rendered.lines().nth(7), Some("          '_   v   _'"));
+/// ```
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Mouth {
+    /// The mouth the speaker was drawn with
+    #[default]
+    Neutral,
+    Smile,
+    Frown,
+    Open,
+    Surprised,
+    TongueOut,
+}
+
+impl Mouth {
+    /// The name used to pick the mouth, e.g. on the command line
+    pub fn name(&self) -> &'static str {
+        match self {
+            Mouth::Neutral => "neutral",
+            Mouth::Smile => "smile",
+            Mouth::Frown => "frown",
+            Mouth::Open => "open",
+            Mouth::Surprised => "surprised",
+            Mouth::TongueOut => "tongue-out",
+        }
+    }
+
+    /// All the mouths there are
+    pub fn all() -> [Mouth; 6] {
+        [
+            Mouth::Neutral,
+            Mouth::Smile,
+            Mouth::Frown,
+            Mouth::Open,
+            Mouth::Surprised,
+            Mouth::TongueOut,
+        ]
+    }
 }
 
+impl FromStr for Mouth {
+    type Err = Error;
+
+    /// Look up a mouth by its `name`
+    fn from_str(name: &str) -> Result<Mouth> {
+        Mouth::all()
+            .iter()
+            .find(|mouth| mouth.name() == name)
+            .cloned()
+            .ok_or_else(|| Error::UnknownMouth(name.to_owned()))
+    }
+}
+
+#[derive(Clone)]
 pub struct FerrisConfig {
     pub mode: SpeechModes,
-    pub eyes: Eyes
+    pub eyes: Eyes,
+    /// The expression of the mouth
+    pub mouth: Mouth,
+    /// Set the eyes and the mouth all at once the way cowsay does, in place
+    /// of `eyes` and `mouth`
+    pub mood: Option<Mood>,
+    /// A tongue of your own for the `{tongue}` slot of the art, such as
+    /// `"U "`, instead of the one that goes with the mouth
+    pub tongue: Option<String>,
+    pub speaker: Speaker,
+    pub encoding: Encoding,
+    pub wrapping: Wrapping,
+    pub bubble: BubbleStyle,
+    /// Turn the text into capitals, best paired with `SpeechModes::Shout`
+    pub uppercase: bool,
+    /// Paint the picture with ANSI escape codes in these colours
+    pub palette: Option<Palette>,
+    /// The colours the terminal can show, the palette is downsampled to them
+    pub color_support: ColorSupport,
+    /// Paint the text with a colour effect instead of the palette
+    pub effect: Option<Effect>,
+    /// Keep the SGR colour sequences of the input in order, ending them
+    /// before the frame of the bubble and starting them again on the next
+    /// line
+    pub keep_colors: bool,
+    /// What happens to control characters and escape sequences in the text
+    pub controls: Controls,
+    /// Turn the picture around so the speaker stands on the right, e.g. for
+    /// right-to-left text
+    pub mirror: bool
+}
+
+impl Default for FerrisConfig {
+    fn default() -> Self {
+        FerrisConfig {
+            mode: SpeechModes::Say,
+            eyes: Eyes::RegularEyes,
+            mouth: Mouth::Neutral,
+            mood: None,
+            tongue: None,
+            speaker: Speaker::Ferris,
+            encoding: Encoding::Utf8,
+            wrapping: Wrapping::default(),
+            bubble: BubbleStyle::Classic,
+            uppercase: false,
+            palette: None,
+            color_support: ColorSupport::TrueColor,
+            effect: None,
+            keep_colors: false,
+            controls: Controls::Keep,
+            mirror: false
+        }
+    }
 }
 
 // Constants! :D
-const ENDSL: &[u8] = b"| ";
-const ENDSR: &[u8] = b" |\n";
-const REGULAR_EYES: &[u8] = b"o";
-const GREEDY_EYES: &[u8] = b"$";
-const YOUTHFUL_EYES: &[u8] = b".";
-const PARANOID_EYES: &[u8] = b"@";
-const DEAD_EYES: &[u8] = b"x";
-const TIRED_EYES: &[u8] = b"-";
-const CRYING_EYES: &[u8] = b"T";
-const HAPPY_EYES: &[u8] = b"^";
-
-const FERRIS_TOP: &[u8] = br#"
-            _~^~^~_
-        \) /  "#;
-const FERRIS_BOTTOM: &[u8] = br#"  \ (/
-          '_   -   _'
-          / '-----' \
-"#;
-
-const COW_TOP: &[u8] = br#"
-            ^__^
-            ("#;
-const COW_BOTTOM: &[u8] = br#")\_______
-            (__)\       )\/\
-                ||----w |
-                ||     ||
-"#;
-
-const CLIPPY_TOP: &[u8] = br#"
-            __
-           /  \
-           |  |
-           "#;
-const CLIPPY_BOTTOM: &[u8] = br#"
-           |  |
-           || |/
-           || ||
-           |\_/|
-           \___/
-"#;
-
-const SPEECH_BUBBLE: &[u8] = br#"
-        \
-         \"#;
-const THOUGHT_BUBBLE: &[u8] = br#"
-        o
-         o"#;
-
-const NEWLINE: u8 = b'\n';
-const DASH: u8 = b'-';
-const UNDERSCORE: u8 = b'_';
-
-// A decent number for SmallVec's Buffer Size, not too large
-// but also big enough for most inputs
-const BUFSIZE: usize = 2048;
+const REGULAR_EYES: &str = "o";
+const GREEDY_EYES: &str = "$";
+const YOUTHFUL_EYES: &str = ".";
+const PARANOID_EYES: &str = "@";
+const DEAD_EYES: &str = "x";
+const TIRED_EYES: &str = "-";
+const CRYING_EYES: &str = "T";
+const HAPPY_EYES: &str = "^";
+
+// The tail goes round these, so a tail can be made of several pieces
+const SPEECH_TAIL: &[&str] = &["\\"];
+const THOUGHT_TAIL: &[&str] = &["o"];
+const SHOUT_TAIL: &[&str] = &["!"];
+const WHISPER_TAIL: &[&str] = &["."];
+const SONG_TAIL: &[&str] = &["♪", "♫"];
+const SLEEP_TAIL: &[&str] = &["z", "Z"];
 
 /// Print out Ferris saying something.
 ///
@@ -115,6 +369,9 @@ const BUFSIZE: usize = 2048;
 ///
 /// `eyes` Ferris has different moods
 ///
+/// Ferris does the talking unless another default speaker was chosen with the
+/// deprecated `set_speaker`, use `perform` to pick the speaker per call.
+///
 /// # Example
 ///
 /// The following bit of code will write the byte string to STDOUT
@@ -144,14 +401,15 @@ const BUFSIZE: usize = 2048;
 ///           '_   -   _'
 ///           / '-----' \
 /// ```
-
 pub fn say<W>(input: &[u8], max_width: usize, writer: &mut W, eyes: &Eyes) -> Result<()>
 where
     W: Write,
 {
     let cfg = FerrisConfig {
         mode: SpeechModes::Say,
-        eyes: *eyes
+        eyes: eyes.clone(),
+        speaker: default_speaker(),
+        ..Default::default()
     };
     perform(input, max_width, writer, &cfg)
 }
@@ -167,6 +425,9 @@ where
 ///
 /// `eyes` Ferris has different moods
 ///
+/// Ferris does the thinking unless another default speaker was chosen with the
+/// deprecated `set_speaker`, use `perform` to pick the speaker per call.
+///
 /// # Example
 ///
 /// The following bit of code will write the byte string to STDOUT
@@ -187,7 +448,7 @@ where
 ///
 /// ```plain
 ///  __________________________
-/// < Hello fellow Rustaceans! >
+/// ( Hello fellow Rustaceans! )
 ///  --------------------------
 ///         o
 ///          o
@@ -202,7 +463,9 @@ where
 {
     let cfg = FerrisConfig {
         mode: SpeechModes::Think,
-        eyes: *eyes
+        eyes: eyes.clone(),
+        speaker: default_speaker(),
+        ..Default::default()
     };
     perform(input, max_width, writer, &cfg)
 }
@@ -216,7 +479,11 @@ where
 /// `writer` is anywhere that can be written to using the Writer trait like
 /// STDOUT or STDERR
 ///
-/// `cfg` Ferris can have different moods and also likes to think sometimes
+/// `cfg` Ferris can have different moods and also likes to think sometimes.
+/// The encoding of `input` is part of the configuration, by default it has to
+/// be UTF-8.
+/// The speaker is part of the configuration as well, so rendering different
+/// speakers from several threads at once is fine.
 ///
 /// # Example
 ///
@@ -233,7 +500,9 @@ where
 /// let mut writer = BufWriter::new(stdout.lock());
 /// let ferris_cfg = FerrisConfig {
 ///     mode: SpeechModes::Think,
-///     eyes: Eyes::HappyEyes
+///     eyes: Eyes::HappyEyes,
+///     speaker: Speaker::Ferris,
+///     ..Default::default()
 /// };
 /// perform(out, width, &mut writer, &ferris_cfg).unwrap();
 /// ```
@@ -242,7 +511,7 @@ where
 ///
 /// ```plain
 ///  __________________________
-/// < Hello fellow Rustaceans! >
+/// ( Hello fellow Rustaceans! )
 ///  --------------------------
 ///         o
 ///          o
@@ -255,104 +524,151 @@ pub fn perform<W>(input: &[u8], max_width: usize, writer: &mut W, cfg: &FerrisCo
 where
     W: Write,
 {
-    // Final output is stored here
-    let mut write_buffer = SmallVec::<[u8; BUFSIZE]>::new();
+    let rendered = render(input, max_width, cfg)?;
+    writer.write_all(rendered.text().as_bytes())?;
+    Ok(())
+}
 
-    // Let textwrap work its magic
-    let wrapped = fill(
-        str::from_utf8(input).map_err(|_| std::io::ErrorKind::InvalidData)?,
-        max_width,
-    );
-
-    let lines: Vec<&str> = wrapped.lines().collect();
-
-    let line_count = lines.len();
-    let actual_width = longest_line(&lines);
-
-    let mut top_bar_buffer: Vec<u8> = repeat(UNDERSCORE).take(actual_width + 2).collect();
-    top_bar_buffer.insert(0, b' ');
-
-    let mut bottom_bar_buffer: Vec<u8> = repeat(DASH).take(actual_width + 2).collect();
-    bottom_bar_buffer.insert(0, b' ');
-
-    write_buffer.extend_from_slice(&top_bar_buffer);
-    write_buffer.push(NEWLINE);
-
-    for (current_line, line) in lines.into_iter().enumerate() {
-        if line_count == 1 {
-            write_buffer.extend_from_slice(b"< ");
-        } else if current_line == 0 {
-            write_buffer.extend_from_slice(b"/ ");
-        } else if current_line == line_count - 1 {
-            write_buffer.extend_from_slice(b"\\ ");
-        } else {
-            write_buffer.extend_from_slice(ENDSL);
-        }
+/// Let Ferris say or think something and get the result back
+///
+/// Takes the same arguments as `perform`, but instead of writing the output
+/// somewhere it is returned as a `Rendered` value, ready to be put into log
+/// messages, chat messages or test assertions.
+///
+/// Fails with `Error::InvalidUtf8` or `Error::InvalidUtf16` if `input` can't
+/// be decoded with the encoding of `cfg` and with `Error::InvalidWidth` if
+/// `max_width` is 0.
+///
+/// # Example
+///
+/// ```rust
+/// use ferris_says::*;
+///
+/// let rendered = render(b"Hello fellow Rustaceans!", 24, &FerrisConfig::default()).unwrap();
+///
+/// assert_eq!(rendered.lines().next(), Some(" __________________________"));
+/// assert_eq!(rendered.width(), 28);
+/// assert_eq!(rendered.height(), 9);
+/// println!("{}", rendered);
+/// ```
+pub fn render(input: &[u8], max_width: usize, cfg: &FerrisConfig) -> Result<Rendered> {
+    let text = cfg.encoding.decode(input)?;
+    if max_width == 0 {
+        return Err(Error::InvalidWidth(max_width));
+    }
 
-        let line_len = UnicodeWidthStr::width(line);
-        write_buffer.extend_from_slice(line.as_bytes());
-        for _i in line_len..actual_width {
-            write_buffer.extend_from_slice(b" ");
-        }
+    let mut rendered = String::new();
+    draw(&text, max_width, cfg, &mut rendered).expect("Writing to a String can't fail");
+    Ok(Rendered::new(rendered))
+}
 
-        if line_count == 1 {
-            write_buffer.extend_from_slice(b" >\n");
-        } else if current_line == 0 {
-            write_buffer.extend_from_slice(b" \\\n");
-        } else if current_line == line_count - 1 {
-            write_buffer.extend_from_slice(b" /\n");
-        } else {
-            write_buffer.extend_from_slice(ENDSR);
-        }
-    }
+fn draw<W>(text: &str, max_width: usize, cfg: &FerrisConfig, out: &mut W) -> fmt::Result
+where
+    W: fmt::Write,
+{
+    let text = ansi::sanitize(text, cfg.controls, cfg.keep_colors);
 
-    write_buffer.extend_from_slice(&bottom_bar_buffer);
-    let FerrisConfig { mode, eyes } = cfg;
+    // Capitals can be wider, so this happens before wrapping
+    let text = if cfg.uppercase {
+        Cow::Owned(ansi::to_uppercase(&text))
+    } else {
+        text
+    };
 
-    match mode {
-        SpeechModes::Say =>  write_buffer.extend_from_slice(SPEECH_BUBBLE),
-        SpeechModes::Think => write_buffer.extend_from_slice(THOUGHT_BUBBLE),
+    // Let textwrap work its magic
+    let mut wrapped = wrap::wrap(&text, max_width, &cfg.wrapping);
+    if cfg.keep_colors {
+        ansi::balance(&mut wrapped);
     }
 
-    let eye = match eyes {
-        Eyes::CryingEyes => CRYING_EYES,
-        Eyes::DeadEyes => DEAD_EYES,
-        Eyes::RegularEyes => REGULAR_EYES,
-        Eyes::GreedyEyes => GREEDY_EYES,
-        Eyes::ParanoidEyes => PARANOID_EYES,
-        Eyes::YouthfulEyes => YOUTHFUL_EYES,
-        Eyes::TiredEyes => TIRED_EYES,
-        Eyes::HappyEyes => HAPPY_EYES,
+    let lines: Vec<&str> = wrapped.iter().map(String::as_str).collect();
+    let palette = cfg
+        .palette
+        .map(|palette| palette.downsample(cfg.color_support))
+        .unwrap_or_default();
+    let brush = cfg.effect.map(|effect| Brush {
+        effect,
+        support: cfg.color_support,
+    });
+    let mut bubble = String::new();
+    bubble::draw(&mut bubble, &lines, cfg.bubble.frame(cfg.mode), &palette, brush.as_ref())?;
+
+    let FerrisConfig { mode, mood, tongue, speaker, mirror, .. } = cfg;
+
+    let tail = match mode {
+        SpeechModes::Say => SPEECH_TAIL,
+        SpeechModes::Think => THOUGHT_TAIL,
+        SpeechModes::Shout => SHOUT_TAIL,
+        SpeechModes::Whisper => WHISPER_TAIL,
+        SpeechModes::Sing => SONG_TAIL,
+        SpeechModes::Sleep => SLEEP_TAIL,
     };
 
-    let mutex_guard = SPEAKER.lock().expect("Could not retrieve speaker");
-    let speaker = *mutex_guard;
-    std::mem::drop(mutex_guard);
-    let (bottom_str, top_str, eye_gap) = match speaker {
-        Speaker::Ferris => (FERRIS_BOTTOM, FERRIS_TOP, " "),
-        Speaker::Clippy => (CLIPPY_BOTTOM, CLIPPY_TOP, "  "),
-        Speaker::Cow => (COW_BOTTOM, COW_TOP, ""),
+    let (eyes, mouth) = match mood {
+        Some(mood) => (Cow::Owned(mood.eyes()), mood.mouth()),
+        None => (Cow::Borrowed(&cfg.eyes), cfg.mouth),
+    };
+    let eyes = if *mode == SpeechModes::Sleep {
+        // Nobody sleeps with their eyes open
+        (TIRED_EYES, TIRED_EYES)
+    } else {
+        eyes.glyphs()
+    };
+    let (mouth, own_tongue) = speaker.expression(mouth);
+    let face = Face {
+        eyes,
+        mouth,
+        tongue: tongue.as_deref().unwrap_or(own_tongue),
+        accessory: mood.and_then(|mood| mood.accessory()).unwrap_or(""),
     };
 
-    write_buffer.extend_from_slice(top_str);
-    write_buffer.extend_from_slice(eye);
-    write_buffer.extend_from_slice(eye_gap.as_bytes());
-    write_buffer.extend_from_slice(eye);
-    write_buffer.extend_from_slice(bottom_str);
+    let mut art = String::new();
+    match brush {
+        Some(ref brush) if brush.effect.speaker => {
+            let mut plain = String::new();
+            character::draw(&mut plain, speaker, tail, &face, &Palette::default(), *mirror)?;
+            brush.paint(&mut art, plain.trim_end_matches('\n'))?;
+            art.push('\n');
+        }
+        _ => character::draw(&mut art, speaker, tail, &face, &palette, *mirror)?,
+    }
 
-    writer.write_all(&write_buffer)?;
+    if !mirror {
+        out.write_str(&bubble)?;
+        return out.write_str(&art);
+    }
+    // The speaker stands on the right, so both line up on the right
+    let width = |text: &str| text.lines().map(display_width).max().unwrap_or(0);
+    let scene = width(&bubble).max(width(&art));
+    indent(out, &bubble, scene - width(&bubble))?;
+    indent(out, &art, scene - width(&art))
+}
+
+// Write `text` with every line that isn't empty moved `columns` to the right
+fn indent<W: fmt::Write>(out: &mut W, text: &str, columns: usize) -> fmt::Result {
+    for line in text.split_inclusive('\n') {
+        if line != "\n" {
+            out.write_str(&" ".repeat(columns))?;
+        }
+        out.write_str(line)?;
+    }
     Ok(())
 }
 
-/// Ferris has some friends and they can say something as well
+/// Choose who talks when calling `say` or `think`
 ///
 /// `speaker` One of Ferris friends
 ///
+/// This changes a process wide default and will race with other threads doing
+/// the same, set the `speaker` field of `FerrisConfig` and call `perform`
+/// instead.
+///
 /// # Example
 ///
 /// The following bit of code makes Clippy say something
 ///
 /// ```rust
+/// # #![allow(deprecated)]
 /// use ferris_says::*;
 /// use std::io::{ stdout, BufWriter };
 ///
@@ -360,13 +676,9 @@ where
 /// let out = b"Hello fellow Rustaceans!";
 /// let width = 24;
 ///
-/// set_speaker(&Speaker::Clippy);
+/// set_speaker(&Speaker::Clippy).unwrap();
 /// let mut writer = BufWriter::new(stdout.lock());
-/// let ferris_cfg = FerrisConfig {
-///     mode: SpeechModes::Think,
-///     eyes: Eyes::HappyEyes
-/// };
-/// perform(out, width, &mut writer, &ferris_cfg).unwrap();
+/// say(out, width, &mut writer, &Eyes::HappyEyes).unwrap();
 /// ```
 ///
 /// This will print out:
@@ -375,26 +687,26 @@ where
 ///  __________________________
 /// < Hello fellow Rustaceans! >
 ///  --------------------------
-///         o
-///          o
-///             _~^~^~_
-///         \) /  ^ ^  \ (/
-///           '_   -   _'
-///           / '-----' \
+///         \
+///          \
+///             __
+///            /  \
+///            |  |
+///            ^  ^
+///            |  |
+///            || |/
+///            || ||
+///            |\_/|
+///            \___/
 /// ```
+#[deprecated(since = "0.3.0", note = "set the `speaker` field of `FerrisConfig` and use `perform`")]
 pub fn set_speaker(speaker: &Speaker) -> Result<()> {
-    let mut mutex_guard = SPEAKER.lock().expect("Error {} acquiring the Mutex for the speaker");
-    *mutex_guard = *speaker;
+    // A speaker is always valid, even if someone panicked while holding the lock
+    let mut mutex_guard = SPEAKER.lock().unwrap_or_else(PoisonError::into_inner);
+    *mutex_guard = speaker.clone();
     Ok(())
 }
 
-fn longest_line(lines: &[&str]) -> usize {
-    let mut max_width = 0;
-    for line in lines {
-        let line_width = UnicodeWidthStr::width(line.to_owned());
-        if line_width > max_width {
-            max_width = line_width;
-        }
-    }
-    max_width
+fn default_speaker() -> Speaker {
+    SPEAKER.lock().unwrap_or_else(PoisonError::into_inner).clone()
 }
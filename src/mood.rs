@@ -0,0 +1,124 @@
+use std::str::FromStr;
+
+use super::{Error, Eyes, Mouth, Result};
+
+/// The faces cowsay makes with `-b`, `-d`, `-g`, `-p`, `-s`, `-t`, `-w` and
+/// `-y`
+///
+/// A mood sets the eyes, the mouth and anything the speaker wears with it all
+/// at once, taking the place of `eyes` and `mouth` in `FerrisConfig`. A
+/// tongue of your own still wins over the one of the mood.
+///
+/// # Example
+///
+/// ```rust
+/// use ferris_says::*;
+///
+/// let cfg = FerrisConfig { mood: Some(Mood::Dead), ..Default::default() };
+/// let rendered = render(b"Hi", 40, &cfg).unwrap();
+/// assert_eq!(rendered.lines().nth(6), Some("        \\) /  x x  \\ (/"));
+/// assert_eq!(rendered.lines().nth(7), Some("          '_   P   _'"));
+/// ```
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Mood {
+    /// Resistance is futile
+    Borg,
+    Dead,
+    Greedy,
+    Paranoid,
+    Stoned,
+    Tired,
+    Wired,
+    Youthful,
+}
+
+impl Mood {
+    /// The name used to pick the mood, e.g. on the command line
+    pub fn name(&self) -> &'static str {
+        match self {
+            Mood::Borg => "borg",
+            Mood::Dead => "dead",
+            Mood::Greedy => "greedy",
+            Mood::Paranoid => "paranoid",
+            Mood::Stoned => "stoned",
+            Mood::Tired => "tired",
+            Mood::Wired => "wired",
+            Mood::Youthful => "youthful",
+        }
+    }
+
+    /// All the moods there are
+    pub fn all() -> [Mood; 8] {
+        [
+            Mood::Borg,
+            Mood::Dead,
+            Mood::Greedy,
+            Mood::Paranoid,
+            Mood::Stoned,
+            Mood::Tired,
+            Mood::Wired,
+            Mood::Youthful,
+        ]
+    }
+
+    /// The flag cowsay picks the mood with, e.g. `'d'` for `-d`
+    pub fn cowsay_flag(&self) -> char {
+        match self {
+            Mood::Borg => 'b',
+            Mood::Dead => 'd',
+            Mood::Greedy => 'g',
+            Mood::Paranoid => 'p',
+            Mood::Stoned => 's',
+            Mood::Tired => 't',
+            Mood::Wired => 'w',
+            Mood::Youthful => 'y',
+        }
+    }
+
+    /// The eyes that go with the mood
+    pub fn eyes(&self) -> Eyes {
+        match self {
+            Mood::Borg => Eyes::custom("=", "="),
+            Mood::Dead => Eyes::DeadEyes,
+            Mood::Greedy => Eyes::GreedyEyes,
+            Mood::Paranoid => Eyes::ParanoidEyes,
+            Mood::Stoned => Eyes::custom("*", "*"),
+            Mood::Tired => Eyes::TiredEyes,
+            Mood::Wired => Eyes::custom("O", "O"),
+            Mood::Youthful => Eyes::YouthfulEyes,
+        }
+    }
+
+    /// The mouth that goes with the mood, the tongue hangs out where cowsay
+    /// has it do so
+    pub fn mouth(&self) -> Mouth {
+        match self {
+            Mood::Dead | Mood::Stoned => Mouth::TongueOut,
+            _ => Mouth::Neutral,
+        }
+    }
+
+    /// What the speaker wears next to its head, for speakers with an
+    /// `{accessory}` in their art
+    pub fn accessory(&self) -> Option<&'static str> {
+        match self {
+            // A wisp of smoke and a spark
+            Mood::Stoned => Some(" ~"),
+            Mood::Wired => Some(" !"),
+            _ => None,
+        }
+    }
+}
+
+impl FromStr for Mood {
+    type Err = Error;
+
+    /// Look up a mood by its `name`
+    fn from_str(name: &str) -> Result<Mood> {
+        Mood::all()
+            .iter()
+            .find(|mood| mood.name() == name)
+            .cloned()
+            .ok_or_else(|| Error::UnknownMood(name.to_owned()))
+    }
+}
@@ -0,0 +1,73 @@
+use std::fmt;
+use std::str::Lines;
+
+use super::width::display_width;
+
+/// Ferris and the speech bubble, drawn into a `String`
+///
+/// Returned by `render`, use `text` or `Display` to get the whole picture and
+/// `lines` to go through it line by line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rendered {
+    text: String,
+}
+
+impl Rendered {
+    pub(crate) fn new(text: String) -> Rendered {
+        Rendered { text }
+    }
+
+    /// All of the output, including the trailing newline
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// The output line by line, without the newlines
+    pub fn lines(&self) -> Lines<'_> {
+        self.text.lines()
+    }
+
+    /// The width in columns of the widest line, colours don't count
+    pub fn width(&self) -> usize {
+        self.lines()
+            .map(display_width)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// The lines filled up with spaces to the width of the widest, handy for
+    /// putting pictures next to each other
+    pub fn padded_lines(&self) -> impl Iterator<Item = String> + '_ {
+        let width = self.width();
+        self.lines()
+            .map(move |line| format!("{}{}", line, " ".repeat(width - display_width(line))))
+    }
+
+    /// The number of lines
+    pub fn height(&self) -> usize {
+        self.lines().count()
+    }
+
+    /// Take the output as a `String`
+    pub fn into_string(self) -> String {
+        self.text
+    }
+}
+
+impl fmt::Display for Rendered {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+impl AsRef<str> for Rendered {
+    fn as_ref(&self) -> &str {
+        &self.text
+    }
+}
+
+impl From<Rendered> for String {
+    fn from(rendered: Rendered) -> String {
+        rendered.text
+    }
+}
@@ -0,0 +1,226 @@
+use std::error;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use super::character::{tokens, Token, PLACEHOLDERS};
+use super::{Character, CowFile, Error, Speaker};
+
+/// A speaker loaded from a ferris-says speaker file
+///
+/// A speaker file starts with a small header of `key: value` lines, followed
+/// by a line of three dashes and the art of the speaker:
+///
+/// ```plain
+/// name: ferris
+/// author: Ferris
+/// description: Ferris the Rustacean
+/// mouth: -
+/// ---
+///         {tail}
+///          {tail}
+///             _~^~^~_
+///         \) /  {left_eye} {right_eye}  \ (/
+///           '_   {mouth}   _'
+///           / '-----' \
+/// ```
+///
+/// The header knows about `name`, `author`, `description`, `tongue` and
+/// `mouth`, all of them are optional. Empty lines and lines starting with `#`
+/// are skipped. The art uses the placeholders described for `Character`, it
+/// has to contain at least one `{tail}` so the bubble is connected to the
+/// speaker.
+pub struct SpeakerFile {
+    name: String,
+    author: Option<String>,
+    description: Option<String>,
+    tongue: String,
+    mouth: String,
+    template: String,
+}
+
+/// The reasons a speaker file can't be turned into a speaker
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SpeakerFileError {
+    /// There is no `---` line between the header and the art
+    MissingSeparator,
+    /// A header line which isn't of the form `key: value`
+    MalformedHeader { line: usize },
+    /// A header key other than the documented ones
+    UnknownKey { line: usize, key: String },
+    /// A placeholder which isn't known to `Character`
+    UnknownPlaceholder { line: usize, placeholder: String },
+    /// The art doesn't say where the bubble tail goes
+    MissingTail,
+}
+
+impl fmt::Display for SpeakerFileError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SpeakerFileError::MissingSeparator => {
+                write!(f, "no `---` line between the header and the art")
+            }
+            SpeakerFileError::MalformedHeader { line } => {
+                write!(f, "line {}: expected a `key: value` header line", line)
+            }
+            SpeakerFileError::UnknownKey { line, key } => write!(
+                f,
+                "line {}: unknown header key `{}`, expected one of name, author, description, tongue or mouth",
+                line, key
+            ),
+            SpeakerFileError::UnknownPlaceholder { line, placeholder } => write!(
+                f,
+                "line {}: unknown placeholder `{{{}}}`, expected one of {}",
+                line,
+                placeholder,
+                PLACEHOLDERS.join(", ")
+            ),
+            SpeakerFileError::MissingTail => {
+                write!(f, "the art has no `{{tail}}` placeholder for the bubble tail")
+            }
+        }
+    }
+}
+
+impl error::Error for SpeakerFileError {}
+
+impl SpeakerFile {
+    /// Parse a speaker file, `name` is used unless the header names the speaker
+    pub fn parse(name: &str, source: &str) -> Result<SpeakerFile, SpeakerFileError> {
+        let mut speaker = SpeakerFile {
+            name: name.to_owned(),
+            author: None,
+            description: None,
+            tongue: String::from("  "),
+            mouth: String::from("-"),
+            template: String::new(),
+        };
+
+        let mut lines = source.lines().enumerate().map(|(i, line)| (i + 1, line));
+        let mut separated = false;
+        for (number, line) in lines.by_ref() {
+            let header = line.trim();
+            if header == "---" {
+                separated = true;
+                break;
+            }
+            if header.is_empty() || header.starts_with('#') {
+                continue;
+            }
+
+            let (key, value) = match header.find(':') {
+                Some(colon) => (header[..colon].trim(), header[colon + 1..].trim()),
+                None => return Err(SpeakerFileError::MalformedHeader { line: number }),
+            };
+            match key {
+                "name" => speaker.name = value.to_owned(),
+                "author" => speaker.author = Some(value.to_owned()),
+                "description" => speaker.description = Some(value.to_owned()),
+                // Keep the trailing whitespace of these, it matters in the art
+                "tongue" => speaker.tongue = raw_value(line),
+                "mouth" => speaker.mouth = raw_value(line),
+                _ => {
+                    return Err(SpeakerFileError::UnknownKey {
+                        line: number,
+                        key: key.to_owned(),
+                    })
+                }
+            }
+        }
+
+        if !separated {
+            return Err(SpeakerFileError::MissingSeparator);
+        }
+
+        let mut has_tail = false;
+        for (number, line) in lines {
+            for token in tokens(line) {
+                match token {
+                    Token::Placeholder("tail") => has_tail = true,
+                    Token::Placeholder(placeholder) if !PLACEHOLDERS.contains(&placeholder) => {
+                        return Err(SpeakerFileError::UnknownPlaceholder {
+                            line: number,
+                            placeholder: placeholder.to_owned(),
+                        })
+                    }
+                    _ => {}
+                }
+            }
+            speaker.template.push_str(line);
+            speaker.template.push('\n');
+        }
+
+        if !has_tail {
+            return Err(SpeakerFileError::MissingTail);
+        }
+        Ok(speaker)
+    }
+
+    /// Load a speaker file from disk
+    ///
+    /// Unless the header says otherwise, the speaker is named after the file.
+    /// Files that can't be parsed result in an `Error::SpeakerFile` wrapping the
+    /// `SpeakerFileError`.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<SpeakerFile, Error> {
+        let path = path.as_ref();
+        let source = fs::read_to_string(path)?;
+
+        SpeakerFile::parse(&file_stem(path), &source).map_err(|source| Error::SpeakerFile {
+            path: path.to_owned(),
+            source,
+        })
+    }
+}
+
+impl Character for SpeakerFile {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn template(&self) -> &str {
+        &self.template
+    }
+
+    fn tongue(&self) -> &str {
+        &self.tongue
+    }
+
+    fn mouth(&self) -> &str {
+        &self.mouth
+    }
+
+    fn author(&self) -> Option<&str> {
+        self.author.as_deref()
+    }
+
+    fn description(&self) -> Option<&str> {
+        self.description.as_deref()
+    }
+}
+
+impl Speaker {
+    /// Load a speaker from a file
+    ///
+    /// Files ending in `.cow` are read as cowsay files with `CowFile`, all
+    /// others as ferris-says speaker files with `SpeakerFile`.
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Speaker, Error> {
+        let path = path.as_ref();
+        if path.extension().is_some_and(|extension| extension == "cow") {
+            CowFile::open(path).map(Speaker::custom)
+        } else {
+            SpeakerFile::open(path).map(Speaker::custom)
+        }
+    }
+}
+
+pub(crate) fn file_stem(path: &Path) -> String {
+    path.file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+// Everything after the first `: ` of a header line, whitespace included
+fn raw_value(line: &str) -> String {
+    let value = &line[line.find(':').map_or(line.len(), |colon| colon + 1)..];
+    value.strip_prefix(' ').unwrap_or(value).to_owned()
+}
@@ -0,0 +1,85 @@
+use std::borrow::Cow;
+
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthChar;
+
+use super::ansi::sequence_len;
+
+/// The number of columns `text` takes up on a terminal
+///
+/// Counts grapheme clusters rather than characters, so emoji joined with
+/// zero width joiners, flags and letters with combining marks come out as
+/// wide as they are drawn. Escape sequences take up no room.
+pub(crate) fn display_width(text: &str) -> usize {
+    clusters(text).map(|(_, width)| width).sum()
+}
+
+/// The grapheme clusters of `text` and their widths, with every escape
+/// sequence or control character as a cluster of its own
+pub(crate) fn clusters(text: &str) -> Clusters<'_> {
+    Clusters { rest: text }
+}
+
+pub(crate) struct Clusters<'a> {
+    rest: &'a str,
+}
+
+impl<'a> Iterator for Clusters<'a> {
+    type Item = (&'a str, usize);
+
+    fn next(&mut self) -> Option<(&'a str, usize)> {
+        let rest = self.rest;
+        let first = rest.chars().next()?;
+        let (cluster, width) = if first.is_control() {
+            (&rest[..sequence_len(rest)], 0)
+        } else {
+            let cluster = rest.graphemes(true).next().unwrap();
+            (cluster, cluster_width(cluster))
+        };
+
+        self.rest = &rest[cluster.len()..];
+        Some((cluster, width))
+    }
+}
+
+fn cluster_width(cluster: &str) -> usize {
+    let is_regional_indicator = |c: char| ('\u{1f1e6}'..='\u{1f1ff}').contains(&c);
+
+    // The variation selector asking for an emoji and the pair of regional
+    // indicators of a flag make two columns, whatever the characters
+    if cluster.contains('\u{fe0f}') || cluster.chars().filter(|&c| is_regional_indicator(c)).count() == 2 {
+        2
+    } else {
+        cluster.chars().map(|c| c.width().unwrap_or(0)).max().unwrap_or(0)
+    }
+}
+
+/// `text` with its tabs replaced by spaces up to the next multiple of
+/// `tab_width` columns
+pub(crate) fn expand_tabs(text: &str, tab_width: usize) -> Cow<'_, str> {
+    if !text.contains('\t') {
+        return Cow::Borrowed(text);
+    }
+
+    let tab_width = tab_width.max(1);
+    let mut expanded = String::with_capacity(text.len());
+    let mut column = 0;
+    for (cluster, width) in clusters(text) {
+        match cluster {
+            "\t" => {
+                let spaces = tab_width - column % tab_width;
+                expanded.extend(std::iter::repeat(' ').take(spaces));
+                column += spaces;
+            }
+            "\n" => {
+                expanded.push('\n');
+                column = 0;
+            }
+            _ => {
+                expanded.push_str(cluster);
+                column += width;
+            }
+        }
+    }
+    Cow::Owned(expanded)
+}
@@ -0,0 +1,479 @@
+use std::str::FromStr;
+
+#[cfg(feature = "hyphenation")]
+use hyphenation::{Language, Load, Standard};
+#[cfg(feature = "hyphenation")]
+use lazy_static::lazy_static;
+use textwrap::core;
+use textwrap::{HyphenSplitter, NoHyphenation, WordSplitter};
+
+use super::bidi;
+use super::width::{clusters, display_width, expand_tabs};
+use super::{Error, Result};
+
+/// How the text is broken into the lines of the bubble
+///
+/// Part of `FerrisConfig`, the default wraps text the way `perform` always
+/// did.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Wrapping {
+    /// What happens to the newlines of the text
+    pub line_breaks: LineBreaks,
+    /// Indent the wrapped lines of list items such as `- item` or `1. item`
+    /// to where the text of the item starts, unless `line_breaks` is
+    /// `LineBreaks::Wrap`
+    pub hanging_indent: bool,
+    /// How the places to break lines at are picked
+    pub algorithm: WrapAlgorithm,
+    /// Break words that don't fit on a line by themselves, such as URLs or
+    /// paths, instead of letting them stick out of the bubble
+    pub break_words: bool,
+    /// Where words may be split to fill up a line
+    pub hyphenation: Hyphenation,
+    /// The columns between tab stops, tabs are replaced by spaces up to the
+    /// next one
+    pub tab_width: usize,
+    /// Lay out right-to-left text such as Hebrew or Arabic with the Unicode
+    /// bidi algorithm and align its paragraphs to the right
+    pub bidi: bool,
+}
+
+impl Default for Wrapping {
+    fn default() -> Self {
+        Wrapping {
+            line_breaks: LineBreaks::Wrap,
+            hanging_indent: false,
+            algorithm: WrapAlgorithm::OptimalFit,
+            break_words: true,
+            hyphenation: Hyphenation::Hyphens,
+            tab_width: 8,
+            bidi: false,
+        }
+    }
+}
+
+/// What happens to the newlines of the text
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum LineBreaks {
+    /// Every line of the text is wrapped on its own
+    #[default]
+    Wrap,
+    /// The lines between blank lines are joined into a paragraph and wrapped
+    /// as a whole, for text that was wrapped at another width before
+    Paragraphs,
+    /// Lines are kept as they are in the text and wrapped lines keep the
+    /// indentation of their line, for lists, poems and notes
+    Preserve,
+    /// Lines are kept exactly as they are and never wrapped, the bubble is as
+    /// wide as the longest of them, like `cowsay -n` does
+    Verbatim,
+}
+
+impl LineBreaks {
+    /// The name used to pick it, e.g. on the command line
+    pub fn name(&self) -> &'static str {
+        match self {
+            LineBreaks::Wrap => "wrap",
+            LineBreaks::Paragraphs => "paragraphs",
+            LineBreaks::Preserve => "preserve",
+            LineBreaks::Verbatim => "verbatim",
+        }
+    }
+
+    /// All the ways to handle newlines
+    pub fn all() -> [LineBreaks; 4] {
+        [
+            LineBreaks::Wrap,
+            LineBreaks::Paragraphs,
+            LineBreaks::Preserve,
+            LineBreaks::Verbatim,
+        ]
+    }
+}
+
+impl FromStr for LineBreaks {
+    type Err = Error;
+
+    /// Look up a way to handle newlines by its `name`
+    fn from_str(name: &str) -> Result<LineBreaks> {
+        LineBreaks::all()
+            .iter()
+            .find(|line_breaks| line_breaks.name() == name)
+            .cloned()
+            .ok_or_else(|| Error::UnknownLineBreaks(name.to_owned()))
+    }
+}
+
+/// How the places to break lines at are picked
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum WrapAlgorithm {
+    /// Fill every line with as many words as fit before going to the next
+    FirstFit,
+    /// Look at the whole paragraph and keep the lines about as long as each
+    /// other, at the cost of shorter lines here and there
+    #[default]
+    OptimalFit,
+}
+
+impl WrapAlgorithm {
+    /// The name used to pick it, e.g. on the command line
+    pub fn name(&self) -> &'static str {
+        match self {
+            WrapAlgorithm::FirstFit => "first-fit",
+            WrapAlgorithm::OptimalFit => "optimal-fit",
+        }
+    }
+
+    /// All the ways to pick line breaks
+    pub fn all() -> [WrapAlgorithm; 2] {
+        [WrapAlgorithm::FirstFit, WrapAlgorithm::OptimalFit]
+    }
+}
+
+impl FromStr for WrapAlgorithm {
+    type Err = Error;
+
+    /// Look up a wrapping algorithm by its `name`
+    fn from_str(name: &str) -> Result<WrapAlgorithm> {
+        WrapAlgorithm::all()
+            .iter()
+            .find(|algorithm| algorithm.name() == name)
+            .cloned()
+            .ok_or_else(|| Error::UnknownWrapAlgorithm(name.to_owned()))
+    }
+}
+
+/// Where words may be split to fill up a line
+///
+/// The line ends in a hyphen wherever a word was split.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Hyphenation {
+    /// Words are only split after the hyphens they contain, as in `long-|term`
+    #[default]
+    Hyphens,
+    /// Words are never split, apart from those broken because of
+    /// `Wrapping::break_words`
+    None,
+    /// Words are split between their syllables, as found in an English
+    /// dictionary
+    #[cfg(feature = "hyphenation")]
+    Dictionary,
+}
+
+#[cfg(not(feature = "hyphenation"))]
+const HYPHENATIONS: &[Hyphenation] = &[Hyphenation::Hyphens, Hyphenation::None];
+#[cfg(feature = "hyphenation")]
+const HYPHENATIONS: &[Hyphenation] = &[
+    Hyphenation::Hyphens,
+    Hyphenation::None,
+    Hyphenation::Dictionary,
+];
+
+impl Hyphenation {
+    /// The name used to pick it, e.g. on the command line
+    pub fn name(&self) -> &'static str {
+        match self {
+            Hyphenation::Hyphens => "hyphens",
+            Hyphenation::None => "none",
+            #[cfg(feature = "hyphenation")]
+            Hyphenation::Dictionary => "dictionary",
+        }
+    }
+
+    /// All the ways to split words, `Dictionary` needs the `hyphenation`
+    /// feature
+    pub fn all() -> &'static [Hyphenation] {
+        HYPHENATIONS
+    }
+}
+
+impl FromStr for Hyphenation {
+    type Err = Error;
+
+    /// Look up a way to split words by its `name`
+    fn from_str(name: &str) -> Result<Hyphenation> {
+        Hyphenation::all()
+            .iter()
+            .find(|hyphenation| hyphenation.name() == name)
+            .cloned()
+            .ok_or_else(|| Error::UnknownHyphenation(name.to_owned()))
+    }
+}
+
+#[cfg(feature = "hyphenation")]
+lazy_static! {
+    // Loading the dictionary takes a while, so it's only done once
+    static ref DICTIONARY: Standard =
+        Standard::from_embedded(Language::EnglishUS).expect("the embedded dictionary is valid");
+}
+
+/// Wrap `text` into lines of at most `width` columns, as far as possible
+pub(crate) fn wrap(text: &str, width: usize, wrapping: &Wrapping) -> Vec<String> {
+    let text = expand_tabs(text, wrapping.tab_width);
+    let is_rtl = |text: &str| wrapping.bidi && bidi::is_rtl(text);
+
+    let mut lines = Vec::new();
+    // Whether each line belongs to a right-to-left paragraph
+    let mut rtl = Vec::new();
+    if wrapping.line_breaks == LineBreaks::Verbatim {
+        for line in text.lines() {
+            lines.push(line.to_owned());
+            rtl.push(is_rtl(line));
+        }
+    } else if wrapping.line_breaks == LineBreaks::Wrap {
+        for line in text.lines() {
+            wrap_line(line, width, ("", ""), wrapping, &mut lines);
+            rtl.resize(lines.len(), is_rtl(line));
+        }
+    } else {
+        for paragraph in paragraphs(&text) {
+            // Exactly one blank line between paragraphs, however many there were
+            if !lines.is_empty() {
+                lines.push(String::new());
+            }
+            for block in blocks(&paragraph, wrapping.line_breaks) {
+                wrap_block(&block, width, wrapping, &mut lines);
+                rtl.resize(lines.len(), is_rtl(&block));
+            }
+        }
+    }
+
+    if wrapping.bidi {
+        bidi::lay_out(&mut lines, &rtl);
+    }
+    lines
+}
+
+// The runs of lines that aren't blank
+fn paragraphs(text: &str) -> Vec<Vec<&str>> {
+    let mut paragraphs = vec![Vec::new()];
+    for line in text.lines() {
+        if !line.trim().is_empty() {
+            paragraphs.last_mut().unwrap().push(line.trim_end());
+        } else if !paragraphs.last().unwrap().is_empty() {
+            paragraphs.push(Vec::new());
+        }
+    }
+    paragraphs.retain(|paragraph| !paragraph.is_empty());
+    paragraphs
+}
+
+// The pieces of a paragraph which are wrapped on their own. A list item
+// always starts a new piece, otherwise lists would be run together.
+fn blocks(paragraph: &[&str], line_breaks: LineBreaks) -> Vec<String> {
+    if line_breaks == LineBreaks::Preserve {
+        return paragraph.iter().map(|&line| line.to_owned()).collect();
+    }
+
+    let mut blocks: Vec<String> = Vec::new();
+    for line in paragraph {
+        match blocks.last_mut() {
+            Some(block) if list_marker(line).is_none() => {
+                block.push(' ');
+                block.push_str(line.trim_start());
+            }
+            _ => blocks.push((*line).to_owned()),
+        }
+    }
+    blocks
+}
+
+fn wrap_block(block: &str, width: usize, wrapping: &Wrapping, lines: &mut Vec<String>) {
+    let text = block.trim_start();
+    let indent = &block[..block.len() - text.len()];
+    let mut subsequent_indent = indent.to_owned();
+    if wrapping.hanging_indent {
+        if let Some(marker) = list_marker(text) {
+            subsequent_indent.push_str(&" ".repeat(marker));
+        }
+    }
+
+    wrap_line(text, width, (indent, &subsequent_indent), wrapping, lines);
+}
+
+/// A piece of a word and the spaces after it, what lines are made of
+#[derive(Debug)]
+struct Fragment<'a> {
+    word: &'a str,
+    width: usize,
+    whitespace: &'a str,
+    /// Drawn instead of the whitespace at the end of a line
+    penalty: &'static str,
+}
+
+impl core::Fragment for Fragment<'_> {
+    fn width(&self) -> usize {
+        self.width
+    }
+
+    // Whitespace is made of spaces and the penalty is `-` or nothing, so
+    // their lengths are their widths
+    fn whitespace_width(&self) -> usize {
+        self.whitespace.len()
+    }
+
+    fn penalty_width(&self) -> usize {
+        self.penalty.len()
+    }
+}
+
+// Wrap a line the way `textwrap::wrap` does, but with widths measured by
+// grapheme cluster. The first line starts with the first of `indents`, the
+// others with the second.
+fn wrap_line(line: &str, width: usize, indents: (&str, &str), wrapping: &Wrapping, lines: &mut Vec<String>) {
+    let initial_width = width.saturating_sub(display_width(indents.0));
+    let subsequent_width = width.saturating_sub(display_width(indents.1));
+
+    let splitter = splitter(wrapping.hyphenation);
+    let mut fragments: Vec<Fragment> = words(line)
+        .flat_map(|fragment| split(fragment, &*splitter))
+        .collect();
+    if wrapping.break_words {
+        fragments = fragments
+            .into_iter()
+            .flat_map(|fragment| break_apart(fragment, subsequent_width))
+            .collect();
+        // Otherwise the first word always ends up on the first line, even
+        // if it was broken for the width of the others
+        if !indents.0.is_empty() {
+            fragments.insert(0, fragment(""));
+        }
+    }
+
+    let line_widths = |i| if i == 0 { initial_width } else { subsequent_width };
+    let wrapped = match wrapping.algorithm {
+        WrapAlgorithm::FirstFit => core::wrap_first_fit(&fragments, line_widths),
+        WrapAlgorithm::OptimalFit => core::wrap_optimal_fit(&fragments, line_widths),
+    };
+
+    for (i, fragments) in wrapped.iter().enumerate() {
+        let (last, rest) = match fragments.split_last() {
+            Some(split) => split,
+            None => {
+                lines.push(String::new());
+                continue;
+            }
+        };
+        let mut wrapped_line = String::from(if i == 0 { indents.0 } else { indents.1 });
+        for fragment in rest {
+            wrapped_line.push_str(fragment.word);
+            wrapped_line.push_str(fragment.whitespace);
+        }
+        wrapped_line.push_str(last.word);
+        wrapped_line.push_str(last.penalty);
+        lines.push(wrapped_line);
+    }
+}
+
+fn splitter(hyphenation: Hyphenation) -> Box<dyn WordSplitter> {
+    match hyphenation {
+        Hyphenation::Hyphens => Box::new(HyphenSplitter),
+        Hyphenation::None => Box::new(NoHyphenation),
+        #[cfg(feature = "hyphenation")]
+        Hyphenation::Dictionary => Box::new(&*DICTIONARY),
+    }
+}
+
+fn fragment(word: &str) -> Fragment<'_> {
+    let trimmed = word.trim_end_matches(' ');
+    Fragment {
+        word: trimmed,
+        width: display_width(trimmed),
+        whitespace: &word[trimmed.len()..],
+        penalty: "",
+    }
+}
+
+// The words of `line` with the spaces after them. Spaces at the start make
+// a word of their own with nothing but whitespace.
+fn words(line: &str) -> impl Iterator<Item = Fragment<'_>> {
+    let mut rest = line;
+    std::iter::from_fn(move || {
+        if rest.is_empty() {
+            return None;
+        }
+        let spaces = rest.find(' ').unwrap_or(rest.len());
+        let end = rest[spaces..]
+            .find(|c: char| c != ' ')
+            .map_or(rest.len(), |word| spaces + word);
+        let (word, tail) = rest.split_at(end);
+        rest = tail;
+        Some(fragment(word))
+    })
+}
+
+// Split a word where `splitter` says it may be
+fn split<'a>(fragment: Fragment<'a>, splitter: &dyn WordSplitter) -> Vec<Fragment<'a>> {
+    let mut pieces = Vec::new();
+    let mut start = 0;
+    for end in splitter.split_points(fragment.word) {
+        let word = &fragment.word[start..end];
+        pieces.push(Fragment {
+            word,
+            width: display_width(word),
+            whitespace: "",
+            penalty: if word.ends_with('-') { "" } else { "-" },
+        });
+        start = end;
+    }
+    let word = &fragment.word[start..];
+    pieces.push(Fragment {
+        word,
+        width: display_width(word),
+        ..fragment
+    });
+    pieces
+}
+
+// Break a word that is too long for a line into pieces that fit, between
+// grapheme clusters
+fn break_apart(fragment: Fragment<'_>, line_width: usize) -> Vec<Fragment<'_>> {
+    if fragment.width <= line_width {
+        return vec![fragment];
+    }
+
+    let mut pieces = Vec::new();
+    let (mut start, mut offset, mut width) = (0, 0, 0);
+    for (cluster, cluster_width) in clusters(fragment.word) {
+        if width > 0 && width + cluster_width > line_width {
+            pieces.push(Fragment {
+                word: &fragment.word[start..offset],
+                width,
+                whitespace: "",
+                penalty: "",
+            });
+            start = offset;
+            width = 0;
+        }
+        width += cluster_width;
+        offset += cluster.len();
+    }
+    pieces.push(Fragment {
+        word: &fragment.word[start..],
+        width,
+        ..fragment
+    });
+    pieces
+}
+
+// The width of the bullet or number a list item starts with, including the
+// whitespace up to the text of the item
+fn list_marker(line: &str) -> Option<usize> {
+    let line = line.trim_start();
+    let marker = if line.starts_with(['-', '*', '+', '•']) {
+        line.chars().next().unwrap().len_utf8()
+    } else {
+        let digits = line.find(|c: char| !c.is_ascii_digit()).unwrap_or(line.len());
+        match line[digits..].chars().next() {
+            Some('.') | Some(')') if digits > 0 => digits + 1,
+            _ => return None,
+        }
+    };
+
+    let rest = &line[marker..];
+    let text = rest.trim_start_matches(' ');
+    if text.len() == rest.len() || text.is_empty() {
+        return None;
+    }
+    Some(display_width(&line[..line.len() - text.len()]))
+}
@@ -1,8 +1,16 @@
+#[macro_use]
 extern crate ferris_says;
 extern crate serial_test;
 
 use serial_test::serial;
-use ferris_says::{say, think, perform, SpeechModes, Eyes, FerrisConfig, Speaker, set_speaker};
+use ferris_says::{
+    say, think, perform, render, SpeechModes, Eyes, FerrisConfig, Speaker, Character, CowFile, CowError,
+    SpeakerFile, SpeakerFileError, Ferris, Rendered, Error, Encoding,
+    LineBreaks, Wrapping, WrapAlgorithm, Hyphenation, BubbleStyle, Frame,
+    Color, Palette, FERRIS_ORANGE, ColorSupport, Effect, EffectColors, Direction,
+    Controls, Mouth, Mood
+};
+use std::fmt::{self, Write};
 
 // Default width when running the binary
 const DEFAULT_WIDTH: usize = 40;
@@ -51,9 +59,14 @@ fn hello_fellow_rustaceans_width_24() -> Result<(), ()> {
         "< Hello fellow Rustaceans! >\n",
         " --------------------------\n",
     ));
+    let thought = String::from(concat!(
+        " __________________________\n",
+        "( Hello fellow Rustaceans! )\n",
+        " --------------------------\n",
+    ));
     let input = b"Hello fellow Rustaceans!";
     let width = 24;
-    generic_tests(speech, width, input);
+    generic_tests(speech, thought, width, input);
     Ok(())
 }
 
@@ -67,9 +80,15 @@ fn hello_fellow_rustaceans_width_12() -> Result<(), ()> {
         "\\ Rustaceans!  /\n",
         " --------------\n"
     ));
+    let thought = String::from(concat!(
+        " ______________\n",
+        "( Hello fellow )\n",
+        "( Rustaceans!  )\n",
+        " --------------\n"
+    ));
     let input = b"Hello fellow Rustaceans!";
     let width = 12;
-    generic_tests(speech, width, input);
+    generic_tests(speech, thought, width, input);
     Ok(())
 }
 
@@ -84,9 +103,17 @@ fn hello_fellow_rustaceans_width_6() -> Result<(), ()> {
         "\\ eans!  /\n",
         " --------\n"
     ));
+    let thought = String::from(concat!(
+        " ________\n",
+        "( Hello  )\n",
+        "( fellow )\n",
+        "( Rustac )\n",
+        "( eans!  )\n",
+        " --------\n"
+    ));
     let input = b"Hello fellow Rustaceans!";
     let width = 6;
-    generic_tests(speech, width, input);
+    generic_tests(speech, thought, width, input);
     Ok(())
 }
 
@@ -106,10 +133,22 @@ fn hello_fellow_rustaceans_width_3() -> Result<(), ()> {
         "\\ s!  /\n",
         " -----\n"
     ));
+    let thought = String::from(concat!(
+        " _____\n",
+        "( Hel )\n",
+        "( lo  )\n",
+        "( fel )\n",
+        "( low )\n",
+        "( Rus )\n",
+        "( tac )\n",
+        "( ean )\n",
+        "( s!  )\n",
+        " -----\n"
+    ));
 
     let input = b"Hello fellow Rustaceans!";
     let width = 3;
-    generic_tests(speech, width, input);
+    generic_tests(speech, thought, width, input);
     Ok(())
 }
 
@@ -122,36 +161,1130 @@ fn multibyte_string() -> Result<(), ()> {
         "< 突然の死👻 >\n",
         " ------------\n"
     ));
+    let thought = String::from(concat!(
+        " ____________\n",
+        "( 突然の死👻 )\n",
+        " ------------\n"
+    ));
     let input = "突然の死👻";
     let width = DEFAULT_WIDTH;
-    generic_tests(speech, width, input.as_bytes());
+    generic_tests(speech, thought, width, input.as_bytes());
+    Ok(())
+}
+
+#[test]
+fn speakers_from_multiple_threads() -> Result<(), ()> {
+    let speech = String::from(concat!(
+        " ____________\n",
+        "< Hello Rust >\n",
+        " ------------\n"
+    ));
+    let top_ferris = std::str::from_utf8(FERRIS_TOP).unwrap();
+    let bottom_ferris = std::str::from_utf8(FERRIS_BOTTOM).unwrap();
+    let top_clippy = std::str::from_utf8(CLIPPY_TOP).unwrap();
+    let bottom_clippy = std::str::from_utf8(CLIPPY_BOTTOM).unwrap();
+    let (ferris, _) = create_ferris(speech.clone(), speech.clone(), top_ferris, "o", " ", bottom_ferris);
+    let (clippy, _) = create_ferris(speech.clone(), speech, top_clippy, "o", "  ", bottom_clippy);
+
+    let handles: Vec<_> = vec![(Speaker::Ferris, ferris), (Speaker::Clippy, clippy)]
+        .into_iter()
+        .map(|(speaker, expected)| {
+            std::thread::spawn(move || {
+                let cfg = FerrisConfig { speaker, ..Default::default() };
+                for _ in 0..100 {
+                    compare_strings_perform(b"Hello Rust", DEFAULT_WIDTH, expected.as_bytes(), &cfg);
+                }
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    Ok(())
+}
+
+struct Ghost;
+
+impl Character for Ghost {
+    fn name(&self) -> &str {
+        "ghost"
+    }
+
+    fn template(&self) -> &str {
+        concat!(
+            "        {tail}\n",
+            "         {tail}  .-.  {{boo}}\n",
+            "            ({left_eye} {right_eye}) {unknown}\n",
+            "            | {tongue}\\\n",
+            "            \\   \\\n",
+            "             `~~~'\n",
+        )
+    }
+
+    fn tongue(&self) -> &str {
+        "O "
+    }
+}
+
+#[test]
+fn custom_character() -> Result<(), ()> {
+    let expected = concat!(
+        " ______\n",
+        "( Boo! )\n",
+        " ------\n",
+        "        o\n",
+        "         o  .-.  {boo}\n",
+        "            (@ @) {unknown}\n",
+        "            | O \\\n",
+        "            \\   \\\n",
+        "             `~~~'\n",
+    );
+    let cfg = FerrisConfig {
+        mode: SpeechModes::Think,
+        eyes: Eyes::ParanoidEyes,
+        speaker: Speaker::custom(Ghost),
+        ..Default::default()
+    };
+    assert_eq!(cfg.speaker.name(), "ghost");
+    compare_strings_perform(b"Boo!", DEFAULT_WIDTH, expected.as_bytes(), &cfg);
+    Ok(())
+}
+
+const TUX_COW: &str = r#"##
+## TuX
+## (c) pborys@p-soft.silesia.linux.org.pl
+##
+$the_cow = <<EOC;
+   $thoughts
+    $thoughts
+        .--.
+       |${eyes}_o |
+       |:_/ |   {$tongue}
+      //   \\ \\
+     (|     | )
+    /'\\_   _/`\\
+    \\___)=(___/ \@
+EOC
+"#;
+
+#[test]
+fn cow_file() -> Result<(), ()> {
+    let expected = concat!(
+        " _____\n",
+        "< Tux >\n",
+        " -----\n",
+        "   \\\n",
+        "    \\\n",
+        "        .--.\n",
+        "       |$$_o |\n",
+        "       |:_/ |   {  }\n",
+        "      //   \\ \\\n",
+        "     (|     | )\n",
+        "    /'\\_   _/`\\\n",
+        "    \\___)=(___/ @\n",
+    );
+    let tux = CowFile::parse("tux", TUX_COW).unwrap();
+    assert_eq!(tux.name(), "tux");
+    let cfg = FerrisConfig {
+        eyes: Eyes::GreedyEyes,
+        speaker: Speaker::custom(tux),
+        ..Default::default()
+    };
+    compare_strings_perform(b"Tux", DEFAULT_WIDTH, expected.as_bytes(), &cfg);
+    Ok(())
+}
+
+const DEFAULT_COW: &str = r#"$the_cow = <<"EOC";
+        $thoughts   ^__^
+         $thoughts  ($eyes)\\_______
+            (__)\\       )\\/\\
+                ||----w |
+                ||     ||
+EOC
+"#;
+
+#[test]
+fn sleeping_cow_file() -> Result<(), ()> {
+    let sleeping = |speaker: &Speaker| {
+        let cfg = FerrisConfig { mode: SpeechModes::Sleep, speaker: speaker.clone(), ..Default::default() };
+        render(b"Zzz", DEFAULT_WIDTH, &cfg).unwrap().lines().skip(3).map(String::from).collect::<Vec<_>>()
+    };
+    let cow = Speaker::custom(CowFile::parse("default", DEFAULT_COW).unwrap());
+    assert_eq!(sleeping(&cow)[..2], [
+        "        z   ^__^",
+        "         Z  (--)\\_______",
+    ]);
+
+    // Every piece of the trail takes one column, so the art next to it stays put
+    for speaker in [cow, Speaker::Cow].iter() {
+        let lines = sleeping(speaker);
+        let head = lines.iter().skip_while(|line| !line.contains("^__^")).take(2).collect::<Vec<_>>();
+        assert_eq!(head[0].find("^__^"), head[1].find("(--)"), "{:?}", head);
+    }
+    Ok(())
+}
+
+#[test]
+fn cow_file_errors() -> Result<(), ()> {
+    assert_eq!(
+        CowFile::parse("empty", "## nothing to see here\n").err(),
+        Some(CowError::MissingCow)
+    );
+    assert_eq!(
+        CowFile::parse("eyes", "$eyes = \"..\";\n$the_cow = <<EOC;\nEOC\n").err(),
+        Some(CowError::UnsupportedStatement { line: 1, statement: String::from("$eyes = \"..\";") })
+    );
+    assert_eq!(
+        CowFile::parse("open", "\n$the_cow = <<\"EOC\";\n  $thoughts\n").err(),
+        Some(CowError::UnterminatedHeredoc { line: 2, terminator: String::from("EOC") })
+    );
+    assert_eq!(
+        CowFile::parse("var", "$the_cow = <<EOC;\n  $thoughts\n  ($eye$eye)\nEOC\n").err(),
+        Some(CowError::UnsupportedVariable { line: 3, variable: String::from("$eye") })
+    );
+    assert_eq!(
+        CowFile::parse("array", "$the_cow = <<EOC;\n  @lines\nEOC\n").err(),
+        Some(CowError::UnsupportedVariable { line: 2, variable: String::from("@lines") })
+    );
+    assert!(CowFile::parse("literal", "$the_cow = <<'EOC';\n  $eye @lines \\\nEOC\n").is_ok());
+    Ok(())
+}
+
+const HAPPY_FERRIS: &str = r#"# Ferris, but happier
+name: happy-ferris
+author: Jane Doe
+description: Ferris in a good mood
+mouth: v
+tongue: U
+---
+        {tail}
+         {tail}
+            _~^~^~_
+        \) /  {left_eye} {right_eye}  \ (/
+          '_   {mouth}   _'  {{{tongue}}}
+          / '-----' \
+"#;
+
+#[test]
+fn speaker_file() -> Result<(), ()> {
+    let expected = concat!(
+        " ______\n",
+        "< Yay! >\n",
+        " ------\n",
+        "        \\\n",
+        "         \\\n",
+        "            _~^~^~_\n",
+        "        \\) /  ^ ^  \\ (/\n",
+        "          '_   v   _'  {U}\n",
+        "          / '-----' \\\n",
+    );
+    let ferris = SpeakerFile::parse("fallback", HAPPY_FERRIS).unwrap();
+    assert_eq!(ferris.name(), "happy-ferris");
+    assert_eq!(ferris.author(), Some("Jane Doe"));
+    assert_eq!(ferris.description(), Some("Ferris in a good mood"));
+    let cfg = FerrisConfig {
+        eyes: Eyes::HappyEyes,
+        speaker: Speaker::custom(ferris),
+        ..Default::default()
+    };
+    compare_strings_perform(b"Yay!", DEFAULT_WIDTH, expected.as_bytes(), &cfg);
+
+    let path = std::env::temp_dir().join("ferris-says-speaker-file-test.speaker");
+    std::fs::write(&path, "---\n  {tail}\n  ({left_eye}{right_eye})\n").unwrap();
+    let speaker = Speaker::from_file(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(speaker.name(), "ferris-says-speaker-file-test");
+    assert_eq!(speaker.description(), None);
+    Ok(())
+}
+
+#[test]
+fn speaker_file_errors() -> Result<(), ()> {
+    assert_eq!(
+        SpeakerFile::parse("art", "name: art\n").err(),
+        Some(SpeakerFileError::MissingSeparator)
+    );
+    assert_eq!(
+        SpeakerFile::parse("header", "\nname = oops\n---\n{tail}\n").err(),
+        Some(SpeakerFileError::MalformedHeader { line: 2 })
+    );
+    assert_eq!(
+        SpeakerFile::parse("key", "color: orange\n---\n{tail}\n").err(),
+        Some(SpeakerFileError::UnknownKey { line: 1, key: String::from("color") })
+    );
+    assert_eq!(
+        SpeakerFile::parse("placeholder", "---\n{tail}\n{nose}\n").err(),
+        Some(SpeakerFileError::UnknownPlaceholder { line: 3, placeholder: String::from("nose") })
+    );
+    assert_eq!(
+        SpeakerFile::parse("tail", "---\n{{tail}}\n").err(),
+        Some(SpeakerFileError::MissingTail)
+    );
+    Ok(())
+}
+
+#[test]
+fn rendered() -> Result<(), ()> {
+    let cfg = FerrisConfig { speaker: Speaker::Cow, ..Default::default() };
+    let rendered = render("突然の死👻".as_bytes(), DEFAULT_WIDTH, &cfg).unwrap();
+    let lines: Vec<&str> = rendered.lines().collect();
+
+    assert_eq!(lines[1], "< 突然の死👻 >");
+    assert_eq!(lines[4], "         \\");
+    assert_eq!(rendered.height(), 10);
+    assert_eq!(rendered.width(), 28);
+    assert!(rendered.padded_lines().all(|line| line.chars().count() == 28 || line.contains("突然")));
+    assert_eq!(rendered.padded_lines().nth(4).unwrap(), format!("{:28}", "         \\"));
+    assert_eq!(rendered.padded_lines().count(), rendered.height());
+    assert_eq!(format!("{}", rendered), rendered.text());
+    assert_eq!(String::from(rendered.clone()), rendered.into_string());
+    assert!(render(b"\xff", DEFAULT_WIDTH, &cfg).is_err());
+    Ok(())
+}
+
+struct Build {
+    crates: usize,
+}
+
+impl fmt::Debug for Build {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", ferris_say!(width = 12, speaker = Speaker::Cow, "built {} crates", self.crates))
+    }
+}
+
+#[test]
+fn display_adapter() -> Result<(), ()> {
+    let cfg = FerrisConfig {
+        mode: SpeechModes::Think,
+        eyes: Eyes::DeadEyes,
+        speaker: Speaker::Clippy,
+        ..Default::default()
+    };
+    let expected = render(b"Hello fellow Rustaceans!", 12, &cfg).unwrap();
+    let ferris = Ferris::thinks("Hello fellow Rustaceans!")
+        .width(12)
+        .eyes(Eyes::DeadEyes)
+        .speaker(Speaker::Clippy);
+    assert_eq!(ferris.to_string(), expected.text());
+
+    let mut text = String::new();
+    write!(text, "{}", ferris_say!("Hello {} {}!", "fellow", "Rustaceans")).unwrap();
+    assert_eq!(text, render(b"Hello fellow Rustaceans!", DEFAULT_WIDTH, &FerrisConfig::default()).unwrap().text());
+
+    let cow = FerrisConfig { speaker: Speaker::Cow, ..Default::default() };
+    assert_eq!(
+        format!("{:?}", Build { crates: 42 }),
+        render(b"built 42 crates", 12, &cow).unwrap().text()
+    );
+    Ok(())
+}
+
+#[test]
+fn encodings() -> Result<(), ()> {
+    let expected = render("Grüße".as_bytes(), DEFAULT_WIDTH, &FerrisConfig::default()).unwrap();
+    let decoded = |encoding, input: &[u8]| {
+        let cfg = FerrisConfig { encoding, ..Default::default() };
+        render(input, DEFAULT_WIDTH, &cfg).unwrap()
+    };
+
+    assert_eq!(decoded(Encoding::Utf8Lossy, b"\xef\xbb\xbfGr\xc3\xbc\xc3\x9fe"), expected);
+    // Strict UTF-8 keeps a leading U+FEFF
+    assert_eq!(Encoding::Utf8.decode(b"\xef\xbb\xbfGr\xc3\xbc\xc3\x9fe").unwrap(), "\u{feff}Grüße");
+    assert_eq!(decoded(Encoding::Latin1, b"Gr\xfc\xdfe"), expected);
+    assert_eq!(decoded(Encoding::Utf16Le, b"G\0r\0\xfc\0\xdf\0e\0"), expected);
+    assert_eq!(decoded(Encoding::Utf16Be, b"\0G\0r\0\xfc\0\xdf\0e"), expected);
+    assert_eq!(decoded(Encoding::Utf16, b"\xff\xfeG\0r\0\xfc\0\xdf\0e\0"), expected);
+    assert_eq!(decoded(Encoding::Utf16, b"\xfe\xff\0G\0r\0\xfc\0\xdf\0e"), expected);
+    assert_eq!(decoded(Encoding::Utf16, b"G\0r\0\xfc\0\xdf\0e\0"), expected);
+    assert_eq!(
+        decoded(Encoding::Utf8Lossy, b"Gr\xfc\xdfe"),
+        render("Gr\u{fffd}\u{fffd}e".as_bytes(), DEFAULT_WIDTH, &FerrisConfig::default()).unwrap()
+    );
+
+    let utf16 = FerrisConfig { encoding: Encoding::Utf16, ..Default::default() };
+    match render(b"\xff\xfeG\0r", DEFAULT_WIDTH, &utf16) {
+        Err(Error::InvalidUtf16 { offset }) => assert_eq!(offset, 4),
+        other => panic!("expected invalid UTF-16, got {:?}", other),
+    }
+    match render(b"G\0\x00\xd8r\0", DEFAULT_WIDTH, &utf16) {
+        Err(Error::InvalidUtf16 { offset }) => assert_eq!(offset, 2),
+        other => panic!("expected invalid UTF-16, got {:?}", other),
+    }
+    match render(b"\xef\xbb\xbfGr\xfc", DEFAULT_WIDTH, &FerrisConfig::default()) {
+        Err(Error::InvalidUtf8 { offset, .. }) => assert_eq!(offset, 5),
+        other => panic!("expected invalid UTF-8, got {:?}", other),
+    }
+
+    for encoding in Encoding::all().iter() {
+        assert_eq!(encoding.name().parse::<Encoding>().unwrap(), *encoding);
+    }
+    match "ebcdic".parse::<Encoding>() {
+        Err(Error::UnknownEncoding(name)) => assert_eq!(name, "ebcdic"),
+        other => panic!("expected an unknown encoding, got {:?}", other),
+    }
+    Ok(())
+}
+
+#[test]
+fn line_breaks() -> Result<(), ()> {
+    let notes = "Notes\n\n\nThis paragraph was\nwrapped before.\r\n\n- a list item that is long\n- short\n    indented text goes on\n\n";
+    let bubble = |line_breaks, hanging_indent| {
+        let wrapping = Wrapping { line_breaks, hanging_indent, ..Default::default() };
+        let cfg = FerrisConfig { wrapping, ..Default::default() };
+        let rendered = render(notes.as_bytes(), 16, &cfg).unwrap();
+        bubble_lines(&rendered)
+    };
+
+    assert_eq!(bubble(LineBreaks::Paragraphs, true), vec![
+        "/ Notes            \\",
+        "|                  |",
+        "| This paragraph   |",
+        "| was wrapped      |",
+        "| before.          |",
+        "|                  |",
+        "| - a list item    |",
+        "|   that is long   |",
+        "| - short indented |",
+        "\\   text goes on   /",
+    ]);
+    assert_eq!(bubble(LineBreaks::Preserve, true), vec![
+        "/ Notes            \\",
+        "|                  |",
+        "| This paragraph   |",
+        "| was              |",
+        "| wrapped before.  |",
+        "|                  |",
+        "| - a list item    |",
+        "|   that is long   |",
+        "| - short          |",
+        "|     indented     |",
+        "\\     text goes on /",
+    ]);
+    assert_eq!(bubble(LineBreaks::Preserve, false)[6..8], [
+        "| - a list item    |",
+        "| that is long     |",
+    ]);
+    // Nothing is wrapped, blank lines and indentation stay
+    assert_eq!(bubble(LineBreaks::Verbatim, false), vec![
+        "/ Notes                      \\",
+        "|                            |",
+        "|                            |",
+        "| This paragraph was         |",
+        "| wrapped before.            |",
+        "|                            |",
+        "| - a list item that is long |",
+        "| - short                    |",
+        "|     indented text goes on  |",
+        "\\                            /",
+    ]);
+    // Tabs are still expanded
+    let verbatim = FerrisConfig {
+        wrapping: Wrapping { line_breaks: LineBreaks::Verbatim, ..Default::default() },
+        ..Default::default()
+    };
+    assert_eq!(render(b"key\tvalue\n\tindented", 8, &verbatim).unwrap().lines().skip(1).take(2).collect::<Vec<_>>(), vec![
+        "/ key     value    \\",
+        "\\         indented /",
+    ]);
+
+    let wrapped = FerrisConfig::default();
+    let explicit = FerrisConfig {
+        wrapping: Wrapping { line_breaks: LineBreaks::Wrap, hanging_indent: true, ..Default::default() },
+        ..Default::default()
+    };
+    assert_eq!(render(notes.as_bytes(), 16, &explicit).unwrap(), render(notes.as_bytes(), 16, &wrapped).unwrap());
+
+    assert_eq!("preserve".parse::<LineBreaks>().unwrap(), LineBreaks::Preserve);
+    match "keep".parse::<LineBreaks>() {
+        Err(Error::UnknownLineBreaks(name)) => assert_eq!(name, "keep"),
+        other => panic!("expected unknown line breaks, got {:?}", other),
+    }
+    Ok(())
+}
+
+#[test]
+fn wrap_algorithms() -> Result<(), ()> {
+    let bubble = |text: &str, width, wrapping| {
+        let cfg = FerrisConfig { wrapping, ..Default::default() };
+        let rendered = render(text.as_bytes(), width, &cfg).unwrap();
+        bubble_lines(&rendered)
+    };
+
+    let text = "To be, or not to be: that is the question";
+    let first_fit = Wrapping { algorithm: WrapAlgorithm::FirstFit, ..Default::default() };
+    assert_eq!(bubble(text, 10, first_fit), vec![
+        "/ To be, or  \\",
+        "| not to be: |",
+        "| that is    |",
+        "| the        |",
+        "\\ question   /",
+    ]);
+    assert_eq!(bubble(text, 10, Wrapping::default()), vec![
+        "/ To be,    \\",
+        "| or not to |",
+        "| be: that  |",
+        "| is the    |",
+        "\\ question  /",
+    ]);
+
+    let url = "see https://ex.com/long-path";
+    assert_eq!(bubble(url, 12, Wrapping::default()), vec![
+        "/ see          \\",
+        "| https://ex.c |",
+        "\\ om/long-path /",
+    ]);
+    let unbroken = Wrapping { break_words: false, ..Default::default() };
+    assert_eq!(bubble(url, 12, unbroken), vec![
+        "/ see                  \\",
+        "| https://ex.com/long- |",
+        "\\ path                 /",
+    ]);
+    let overflow = Wrapping { break_words: false, hyphenation: Hyphenation::None, ..Default::default() };
+    assert_eq!(bubble(url, 12, overflow), vec![
+        "/ see                      \\",
+        "\\ https://ex.com/long-path /",
+    ]);
+
+    assert_eq!("first-fit".parse::<WrapAlgorithm>().unwrap(), WrapAlgorithm::FirstFit);
+    assert_eq!("none".parse::<Hyphenation>().unwrap(), Hyphenation::None);
+    match "greedy".parse::<WrapAlgorithm>() {
+        Err(Error::UnknownWrapAlgorithm(name)) => assert_eq!(name, "greedy"),
+        other => panic!("expected an unknown wrapping algorithm, got {:?}", other),
+    }
+    Ok(())
+}
+
+#[cfg(feature = "hyphenation")]
+#[test]
+fn dictionary_hyphenation() -> Result<(), ()> {
+    let wrapping = Wrapping { hyphenation: Hyphenation::Dictionary, ..Default::default() };
+    let cfg = FerrisConfig { wrapping, ..Default::default() };
+    let rendered = render(b"extraordinarily", 10, &cfg).unwrap();
+    assert_eq!(rendered.lines().nth(1), Some("/ extraordi- \\"));
+    Ok(())
+}
+
+#[test]
+fn bubble_styles() -> Result<(), ()> {
+    let bubble = |text: &[u8], bubble| {
+        let cfg = FerrisConfig { bubble, ..Default::default() };
+        let rendered = render(text, 12, &cfg).unwrap();
+        rendered.lines().take(rendered.height() - 6).map(String::from).collect::<Vec<_>>()
+    };
+
+    assert_eq!(bubble(b"Hello fellow Rustaceans!", BubbleStyle::Single), vec![
+        "┌──────────────┐",
+        "│ Hello fellow │",
+        "│ Rustaceans!  │",
+        "└──────────────┘",
+    ]);
+    assert_eq!(bubble(b"Hello", BubbleStyle::Double), vec!["╔═══════╗", "║ Hello ║", "╚═══════╝"]);
+    assert_eq!(bubble(b"Hello", BubbleStyle::Rounded), vec!["╭───────╮", "│ Hello │", "╰───────╯"]);
+    assert_eq!(bubble(b"Hello", BubbleStyle::Heavy), vec!["┏━━━━━━━┓", "┃ Hello ┃", "┗━━━━━━━┛"]);
+    assert_eq!(bubble(b"Hello", BubbleStyle::Classic), vec![" _______", "< Hello >", " -------"]);
+
+    const HASHES: Frame = Frame {
+        top_left: "#", top: "#", top_right: "#",
+        bottom_left: "#", bottom: "#", bottom_right: "#",
+        only_line: ["# ", " #"],
+        first_line: ["#/ ", " \\#"],
+        middle_line: ["#| ", " |#"],
+        last_line: ["#\\ ", " /#"],
+    };
+    assert_eq!(bubble(b"one\ntwo\nthree", BubbleStyle::Custom(&HASHES)), vec![
+        "#########",
+        "#/ one   \\#",
+        "#| two   |#",
+        "#\\ three /#",
+        "#########",
+    ]);
+
+    assert_eq!("rounded".parse::<BubbleStyle>().unwrap(), BubbleStyle::Rounded);
+    match "custom".parse::<BubbleStyle>() {
+        Err(Error::UnknownBubbleStyle(name)) => assert_eq!(name, "custom"),
+        other => panic!("expected an unknown bubble style, got {:?}", other),
+    }
     Ok(())
 }
 
+#[test]
+fn thought_cloud() -> Result<(), ()> {
+    let cloud = FerrisConfig { mode: SpeechModes::Think, bubble: BubbleStyle::Cloud, ..Default::default() };
+    let rendered = render(b"Hmm, lunch?", 6, &cloud).unwrap();
+    let lines: Vec<&str> = rendered.lines().take(6).collect();
+    assert_eq!(lines, vec![
+        " ⁀⁀⁀⁀⁀⁀⁀⁀",
+        "( Hmm,   )",
+        "( lunch? )",
+        " ‿‿‿‿‿‿‿‿",
+        "        o",
+        "         o",
+    ]);
+
+    // The tail leaves the cloud at the same place whoever is thinking
+    for speaker in Speaker::builtin().iter() {
+        for bubble in BubbleStyle::builtin().iter() {
+            let cfg = FerrisConfig { mode: SpeechModes::Think, speaker: speaker.clone(), bubble: *bubble, ..Default::default() };
+            let rendered = render(b"Hmm", DEFAULT_WIDTH, &cfg).unwrap();
+            let tail: Vec<&str> = rendered.lines().skip(3).take(2).collect();
+            assert_eq!(tail, vec!["        o", "         o"], "{} in a {} bubble", speaker.name(), bubble.name());
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn speech_modes() -> Result<(), ()> {
+    let top = |mode, uppercase| {
+        let cfg = FerrisConfig { mode, uppercase, ..Default::default() };
+        let rendered = render(b"Hello fellow Rustaceans", 12, &cfg).unwrap();
+        rendered.lines().take(8).map(String::from).collect::<Vec<_>>()
+    };
+
+    assert_eq!(top(SpeechModes::Shout, true), vec![
+        " ^^^^^^^^^^^^^^",
+        "< HELLO FELLOW >",
+        "< RUSTACEANS   >",
+        " vvvvvvvvvvvvvv",
+        "        !",
+        "         !",
+        "            _~^~^~_",
+        "        \\) /  o o  \\ (/",
+    ]);
+    assert_eq!(top(SpeechModes::Whisper, false), vec![
+        " ..............",
+        ": Hello fellow :",
+        ": Rustaceans   :",
+        " ''''''''''''''",
+        "        .",
+        "         .",
+        "            _~^~^~_",
+        "        \\) /  o o  \\ (/",
+    ]);
+    assert_eq!(top(SpeechModes::Sing, false), vec![
+        " ______________",
+        "♪ Hello fellow ♫",
+        "♪ Rustaceans   ♫",
+        " --------------",
+        "        ♪",
+        "         ♫",
+        "            _~^~^~_",
+        "        \\) /  o o  \\ (/",
+    ]);
+    assert_eq!(top(SpeechModes::Sleep, false), vec![
+        " ______________",
+        "( Hello fellow )",
+        "( Rustaceans   )",
+        " --------------",
+        "        z",
+        "         Z",
+        "            _~^~^~_",
+        "        \\) /  - -  \\ (/",
+    ]);
+
+    // Capitals can take up more room than the original
+    let cfg = FerrisConfig { uppercase: true, ..Default::default() };
+    assert_eq!(render("Grüße".as_bytes(), DEFAULT_WIDTH, &cfg).unwrap().lines().nth(1), Some("< GRÜSSE >"));
+
+    for mode in SpeechModes::all().iter() {
+        assert_eq!(mode.name().parse::<SpeechModes>().unwrap(), *mode);
+    }
+    match "yell".parse::<SpeechModes>() {
+        Err(Error::UnknownSpeechMode(name)) => assert_eq!(name, "yell"),
+        other => panic!("expected an unknown speech mode, got {:?}", other),
+    }
+    Ok(())
+}
+
+#[test]
+fn colors() -> Result<(), ()> {
+    for speaker in Speaker::builtin().iter() {
+        let plain = FerrisConfig { speaker: speaker.clone(), ..Default::default() };
+        let painted = FerrisConfig { palette: Some(speaker.palette()), ..plain.clone() };
+        let expected = render(b"Hello fellow Rustaceans!", 12, &plain).unwrap();
+        let rendered = render(b"Hello fellow Rustaceans!", 12, &painted).unwrap();
+        assert_ne!(rendered, expected);
+        assert_eq!(strip_escapes(rendered.text()), expected.text());
+        assert_eq!(rendered.width(), expected.width());
+    }
+
+    let palette = Palette {
+        frame: Some(Color::Blue),
+        text: Some(Color::BrightYellow),
+        tail: Some(Color::Ansi256(208)),
+        speaker: Some(FERRIS_ORANGE),
+    };
+    let cfg = FerrisConfig { palette: Some(palette), ..Default::default() };
+    let rendered = render(b"Hi", DEFAULT_WIDTH, &cfg).unwrap();
+    let lines: Vec<&str> = rendered.lines().collect();
+    assert_eq!(lines[0], "\x1b[34m ____\x1b[0m");
+    assert_eq!(lines[1], "\x1b[34m< \x1b[0m\x1b[93mHi\x1b[0m\x1b[34m >\x1b[0m");
+    assert_eq!(lines[3], "        \x1b[38;5;208m\\\x1b[0m");
+    assert_eq!(lines[5], "\x1b[38;2;247;76;0m            _~^~^~_\x1b[0m");
+
+    // Only the parts with a colour are painted
+    let text_only = Palette { text: Some(Color::Red), ..Default::default() };
+    let rendered = format!("{}", Ferris::says("Hi").palette(text_only));
+    assert_eq!(rendered.lines().nth(1), Some("< \x1b[31mHi\x1b[0m >"));
+    assert_eq!(rendered.matches('\x1b').count(), 2);
+    assert_eq!(Speaker::custom(Ghost).palette(), Palette::default());
+    Ok(())
+}
+
+#[test]
+fn color_support() -> Result<(), ()> {
+    let detect = |vars: &[(&str, &str)]| {
+        ColorSupport::from_env(|name| {
+            vars.iter().find(|(var, _)| *var == name).map(|(_, value)| value.to_string())
+        })
+    };
+    assert_eq!(detect(&[]), ColorSupport::None);
+    assert_eq!(detect(&[("TERM", "dumb")]), ColorSupport::None);
+    assert_eq!(detect(&[("TERM", "xterm")]), ColorSupport::Basic);
+    assert_eq!(detect(&[("TERM", "screen-256color")]), ColorSupport::Ansi256);
+    assert_eq!(detect(&[("TERM", "xterm-direct")]), ColorSupport::TrueColor);
+    assert_eq!(detect(&[("TERM", "xterm-256color"), ("COLORTERM", "truecolor")]), ColorSupport::TrueColor);
+    assert_eq!(detect(&[("COLORTERM", "24bit")]), ColorSupport::TrueColor);
+    assert_eq!(detect(&[("TERM", "xterm-256color"), ("NO_COLOR", "1")]), ColorSupport::None);
+    assert_eq!(detect(&[("TERM", "xterm-256color"), ("NO_COLOR", "")]), ColorSupport::Ansi256);
+
+    assert_eq!(Color::Rgb(0, 0, 0).downsample(ColorSupport::Ansi256), Some(Color::Ansi256(16)));
+    assert_eq!(Color::Rgb(128, 128, 128).downsample(ColorSupport::Ansi256), Some(Color::Ansi256(244)));
+    assert_eq!(Color::Rgb(0, 0, 250).downsample(ColorSupport::Basic), Some(Color::Blue));
+    assert_eq!(Color::Ansi256(196).downsample(ColorSupport::Basic), Some(Color::BrightRed));
+    assert_eq!(Color::Ansi256(3).downsample(ColorSupport::Basic), Some(Color::Yellow));
+    assert_eq!(Color::Cyan.downsample(ColorSupport::Basic), Some(Color::Cyan));
+    assert_eq!(Color::Ansi256(100).downsample(ColorSupport::TrueColor), Some(Color::Ansi256(100)));
+    assert_eq!(Color::Ansi256(231).rgb(), (255, 255, 255));
+    assert_eq!(Color::Ansi256(255).rgb(), (238, 238, 238));
+
+    let painted = |color_support| {
+        let cfg = FerrisConfig { palette: Some(Speaker::Ferris.palette()), color_support, ..Default::default() };
+        render(b"Hi", DEFAULT_WIDTH, &cfg).unwrap()
+    };
+    assert!(painted(ColorSupport::TrueColor).text().starts_with("\x1b[38;2;247;76;0m"));
+    assert!(painted(ColorSupport::Ansi256).text().starts_with("\x1b[38;5;202m"));
+    assert!(painted(ColorSupport::Basic).text().starts_with("\x1b[91m"));
+    assert_eq!(painted(ColorSupport::None), render(b"Hi", DEFAULT_WIDTH, &FerrisConfig::default()).unwrap());
+    Ok(())
+}
+
+#[test]
+fn effects() -> Result<(), ()> {
+    let input = b"Hello fellow Rustaceans!";
+    let plain = render(input, 12, &FerrisConfig::default()).unwrap();
+    let with = |colors, direction, speaker| {
+        let effect = Effect { colors, direction, speaker };
+        render(input, 12, &FerrisConfig { effect: Some(effect), ..Default::default() }).unwrap()
+    };
+    let rainbow = |seed| EffectColors::Rainbow { seed };
+    let gradient = EffectColors::Gradient { from: Color::Rgb(0, 0, 0), to: Color::Rgb(255, 255, 255) };
+
+    for &colors in [rainbow(0), rainbow(42), gradient].iter() {
+        for &direction in [Direction::Horizontal, Direction::Diagonal].iter() {
+            for &speaker in [false, true].iter() {
+                let rendered = with(colors, direction, speaker);
+                // Same every time, in the same places and as wide as without
+                assert_eq!(rendered, with(colors, direction, speaker));
+                assert_eq!(strip_escapes(rendered.text()), plain.text());
+                assert_eq!(rendered.width(), plain.width());
+                assert_ne!(rendered, plain);
+            }
+        }
+    }
+    assert_ne!(with(rainbow(0), Direction::Horizontal, false), with(rainbow(1), Direction::Horizontal, false));
+    // Only the text changes unless the speaker joins in
+    let speaker_lines = |rendered: &ferris_says::Rendered| rendered.lines().skip(3).collect::<Vec<_>>().join("\n");
+    assert_eq!(speaker_lines(&with(gradient, Direction::Horizontal, false)), speaker_lines(&plain));
+    assert_ne!(speaker_lines(&with(gradient, Direction::Horizontal, true)), speaker_lines(&plain));
+
+    // The gradient runs from the first to the last column
+    let rendered = with(gradient, Direction::Horizontal, false);
+    let first = rendered.lines().nth(1).unwrap();
+    assert!(first.starts_with("/ \x1b[38;2;0;0;0mH"));
+    assert!(first.ends_with("\x1b[38;2;255;255;255mw\x1b[0m \\"));
+
+    // Colours the terminal can't show are downsampled, or left out
+    let effect = Effect { colors: rainbow(0), direction: Direction::Horizontal, speaker: false };
+    let cfg = FerrisConfig { effect: Some(effect), color_support: ColorSupport::Ansi256, ..Default::default() };
+    let rendered = render(input, 12, &cfg).unwrap();
+    assert!(rendered.text().contains("\x1b[38;5;") && !rendered.text().contains("\x1b[38;2;"));
+    let cfg = FerrisConfig { color_support: ColorSupport::None, ..cfg };
+    assert_eq!(render(input, 12, &cfg).unwrap(), plain);
+
+    assert_eq!("#f74c00".parse().ok(), Some(FERRIS_ORANGE));
+    assert_eq!("208".parse().ok(), Some(Color::Ansi256(208)));
+    assert_eq!("bright-cyan".parse().ok(), Some(Color::BrightCyan));
+    assert_eq!("red".parse().ok(), Some(Color::Red));
+    assert!(matches!("#f74c0".parse::<Color>(), Err(Error::UnknownColor(_))));
+    assert!(matches!("256".parse::<Color>(), Err(Error::UnknownColor(_))));
+    assert!(matches!("orange".parse::<Color>(), Err(Error::UnknownColor(_))));
+    Ok(())
+}
+
+#[test]
+fn colored_input() -> Result<(), ()> {
+    let input = b"\x1b[1;31merror\x1b[0m: could not compile \x1b[32mferris says hello\x1b[0m today";
+    let cfg = FerrisConfig { keep_colors: true, ..Default::default() };
+    let bubble = |cfg: &FerrisConfig| {
+        let rendered = render(input, 14, cfg).unwrap();
+        bubble_lines(&rendered)
+    };
+    assert_eq!(bubble(&cfg), vec![
+        "/ \x1b[1;31merror\x1b[0m: could \\",
+        "| not compile  |",
+        "| \x1b[32mferris says\x1b[0m  |",
+        "\\ \x1b[32mhello\x1b[0m today  /",
+    ]);
+    // Only the text is coloured, it lines up the same as without colours
+    let plain = render(&strip_escapes(std::str::from_utf8(input).unwrap()).into_bytes(), 14, &cfg).unwrap();
+    assert_eq!(strip_escapes(render(input, 14, &cfg).unwrap().text()), plain.text());
+
+    let cfg = FerrisConfig { uppercase: true, ..cfg };
+    assert_eq!(bubble(&cfg)[0], "/ \x1b[1;31mERROR\x1b[0m: COULD \\");
+
+    let effect = Effect { colors: EffectColors::Rainbow { seed: 0 }, direction: Direction::Horizontal, speaker: false };
+    let painted = FerrisConfig { effect: Some(effect), ..cfg.clone() };
+    let strip = |cfg: &FerrisConfig| strip_escapes(render(input, 14, cfg).unwrap().text());
+    assert_eq!(strip(&painted), strip(&cfg));
+    Ok(())
+}
+
+#[test]
+fn controls() -> Result<(), ()> {
+    let input = "Hi \x1b]0;pwned\x07there \x1b[2J\x1b[31mred\x1b[0m\u{9b}1Aend\r\nok\x01\x1b7!".as_bytes();
+    let bubble = |controls, keep_colors| {
+        let cfg = FerrisConfig { controls, keep_colors, ..Default::default() };
+        let rendered = render(input, 80, &cfg).unwrap();
+        bubble_lines(&rendered)
+    };
+    assert_eq!(bubble(Controls::Strip, false), vec![
+        "/ Hi there redend \\",
+        "\\ ok!             /",
+    ]);
+    assert_eq!(bubble(Controls::Strip, true), vec![
+        "/ Hi there \x1b[31mred\x1b[0mend \\",
+        "\\ ok!             /",
+    ]);
+    assert_eq!(bubble(Controls::Escape, false), vec![
+        "/ Hi ^[]0;pwned^Gthere ^[[2J^[[31mred^[[0m^[[1Aend \\",
+        "\\ ok^A^[7!                                         /",
+    ]);
+    // Nothing is left that the terminal would act on
+    for &controls in [Controls::Strip, Controls::Escape].iter() {
+        let cfg = FerrisConfig { controls, ..Default::default() };
+        let rendered = render(input, 80, &cfg).unwrap();
+        assert!(!rendered.text().chars().any(|c| c.is_control() && c != '\n'));
+    }
+    let cfg = FerrisConfig { controls: Controls::Keep, ..Default::default() };
+    assert!(render(input, 80, &cfg).unwrap().text().contains("\x1b]0;pwned\x07"));
+
+    // Unfinished sequences take the rest of the text with them
+    let cfg = FerrisConfig { controls: Controls::Strip, ..Default::default() };
+    assert_eq!(render(b"Hi\x1b]0;pwned", 80, &cfg).unwrap().lines().nth(1), Some("< Hi >"));
+    assert_eq!("escape".parse::<Controls>().ok(), Some(Controls::Escape));
+    assert!(matches!("scrub".parse::<Controls>(), Err(Error::UnknownControls(_))));
+    Ok(())
+}
+
+#[test]
+fn tabs_and_wide_text() -> Result<(), ()> {
+    let bubble = |input: &str, width, wrapping| {
+        let cfg = FerrisConfig { wrapping, ..Default::default() };
+        let rendered = render(input.as_bytes(), width, &cfg).unwrap();
+        bubble_lines(&rendered)
+    };
+    let preserve = |tab_width| Wrapping { line_breaks: LineBreaks::Preserve, tab_width, ..Default::default() };
+
+    let code = "fn main() {\n\tprintln!(\"hi\");\n}";
+    assert_eq!(bubble(code, 40, preserve(4)), vec![
+        "/ fn main() {         \\",
+        "|     println!(\"hi\"); |",
+        "\\ }                   /",
+    ]);
+    assert_eq!(bubble(code, 40, preserve(8))[1], "|         println!(\"hi\"); |");
+    // Tabs go to the next stop, not a fixed number of spaces
+    assert_eq!(bubble("ab\tc\tdefgh\ti", 40, preserve(4)), vec!["< ab  c   defgh   i >"]);
+
+    // Every line of the bubble is as wide as the others on the terminal, a
+    // speaker without art leaves only the bubble to measure
+    struct Nobody;
+    impl Character for Nobody {
+        fn name(&self) -> &str {
+            "nobody"
+        }
+        fn template(&self) -> &str {
+            ""
+        }
+    }
+    let nobody = FerrisConfig { speaker: Speaker::custom(Nobody), ..Default::default() };
+    let texts = [
+        "日本語のテキストを折り返す",
+        "👨‍👩‍👧 family 🇩🇪 flag ❤️ heart 👋🏽 hi",
+        "cafe\u{301} nai\u{308}ve a\u{30a}ngstro\u{308}m",
+    ];
+    for text in texts.iter() {
+        for &width in [6, 10, 16].iter() {
+            let lines = bubble(text, width, Wrapping::default());
+            let widths: Vec<usize> = lines
+                .iter()
+                .map(|line| render(line.as_bytes(), 80, &nobody).unwrap().width())
+                .collect();
+            assert!(widths.iter().all(|&w| w == widths[0]), "{:?}", lines);
+        }
+    }
+    assert_eq!(bubble("日本語のテキスト", 6, Wrapping::default()), vec![
+        "/ 日本語 \\",
+        "| のテキ |",
+        "\\ スト   /",
+    ]);
+    assert_eq!(bubble("👨‍👩‍👧 family 🇩🇪 flag", 9, Wrapping::default()), vec![
+        "/ 👨‍👩‍👧 family \\",
+        "\\ 🇩🇪 flag   /",
+    ]);
+    assert_eq!(bubble("cafe\u{301} cafe\u{301}", 6, Wrapping::default()), vec![
+        "/ cafe\u{301} \\",
+        "\\ cafe\u{301} /",
+    ]);
+    Ok(())
+}
+
+#[test]
+fn right_to_left() -> Result<(), ()> {
+    let bidi = Wrapping { bidi: true, ..Default::default() };
+    let bubble = |input: &str, width, wrapping: &Wrapping| {
+        let cfg = FerrisConfig { wrapping: wrapping.clone(), ..Default::default() };
+        let rendered = render(input.as_bytes(), width, &cfg).unwrap();
+        bubble_lines(&rendered)
+    };
+
+    // Lines come out in display order, right-to-left paragraphs on the right
+    assert_eq!(bubble("שלום לכולם, מה שלומכם? Rust 2018", 14, &bidi), vec![
+        "/ המ ,םלוכל םולש \\",
+        "|   Rust ?םכמולש |",
+        "\\           2018 /",
+    ]);
+    assert_eq!(bubble("Hello everyone\nשלום", 40, &bidi), vec![
+        "/ Hello everyone \\",
+        "\\           םולש /",
+    ]);
+    assert_eq!(bubble("Say שלום twice", 40, &bidi), vec!["< Say םולש twice >"]);
+    // Nothing changes without right-to-left text or without bidi
+    assert_eq!(bubble("Hello fellow Rustaceans!", 12, &bidi), bubble("Hello fellow Rustaceans!", 12, &Wrapping::default()));
+    assert_eq!(bubble("Hello\nשלום", 40, &Wrapping::default()), vec!["/ Hello \\", "\\ שלום  /"]);
+
+    // The speaker turns around and stands on the right
+    let cfg = FerrisConfig { mirror: true, speaker: Speaker::Cow, ..Default::default() };
+    let rendered = render(b"Moo", 40, &cfg).unwrap();
+    let expected = vec![
+        "              _____",
+        "             < Moo >",
+        "              -----",
+        "                   /",
+        "                  /",
+        "            ^__^",
+        "    _______/(oo)",
+        "/\\/(       /(__)",
+        "   | w----||",
+        "   ||     ||",
+    ];
+    assert_eq!(rendered.lines().collect::<Vec<_>>(), expected);
+    Ok(())
+}
+
+#[test]
+fn custom_eyes() -> Result<(), ()> {
+    let face = |speaker: Speaker, eyes: Eyes, line| {
+        let cfg = FerrisConfig { speaker, eyes, ..Default::default() };
+        render(b"Hi", 40, &cfg).unwrap().lines().nth(line).unwrap().to_owned()
+    };
+
+    assert_eq!(face(Speaker::Ferris, Eyes::custom("^", "o"), 6), "        \\) /  ^ o  \\ (/");
+    assert_eq!(face(Speaker::Ferris, Eyes::custom("ಠ", "ಠ"), 6), "        \\) /  ಠ ಠ  \\ (/");
+    // Wider eyes take the spaces next to them, the rest of the face stays put
+    assert_eq!(face(Speaker::Ferris, Eyes::custom("◉◉", "◉◉"), 6), "        \\) / ◉◉ ◉◉ \\ (/");
+    assert_eq!(face(Speaker::Ferris, Eyes::custom("O", "ＯＯ"), 6), "        \\) /  O ＯＯ \\ (/");
+    // One space is always left, beyond that the line grows
+    assert_eq!(face(Speaker::Ferris, Eyes::custom("OOO", "OOO"), 6), "        \\) / OOO OOO \\ (/");
+    assert_eq!(face(Speaker::Cow, Eyes::custom("^", "o"), 6), "            (^o)\\_______");
+    assert_eq!(face(Speaker::Clippy, Eyes::custom("◉◉", "◉◉"), 8), "          ◉◉  ◉◉");
+    // Missing eyes keep their column
+    assert_eq!(face(Speaker::Ferris, Eyes::custom("", "o"), 6), "        \\) /    o  \\ (/");
+    // Seen in a mirror the wink swaps sides
+    let cfg = FerrisConfig { eyes: Eyes::custom("^", "o"), mirror: true, ..Default::default() };
+    assert!(render(b"Hi", 40, &cfg).unwrap().lines().any(|line| line.ends_with("\\) /  o ^  \\ (/")));
+    // Only the eyes that ship with the crate go by a name
+    for eyes in Eyes::builtin().iter() {
+        assert_eq!(&eyes.name().parse::<Eyes>().unwrap(), eyes);
+    }
+    assert_eq!(Eyes::custom("^", "o").name(), "custom");
+    assert!("custom".parse::<Eyes>().is_err());
+    assert_eq!(Eyes::custom("^", "o").glyphs(), ("^", "o"));
+    assert_eq!(Eyes::HappyEyes.glyphs(), ("^", "^"));
+    // Sleeping shuts them all the same
+    let cfg = FerrisConfig { eyes: Eyes::custom("◉", "◉"), mode: SpeechModes::Sleep, ..Default::default() };
+    assert_eq!(render(b"Hi", 40, &cfg).unwrap().lines().nth(6), Some("        \\) /  - -  \\ (/"));
+    Ok(())
+}
+
+#[test]
+fn mouths() -> Result<(), ()> {
+    let face = |speaker: Speaker, mouth: Mouth, tongue: Option<&str>| {
+        let cfg = FerrisConfig { speaker, mouth, tongue: tongue.map(str::to_owned), ..Default::default() };
+        render(b"Hi", 40, &cfg).unwrap().lines().skip(3).collect::<Vec<_>>().join("\n")
+    };
+
+    // Looking neutral draws the speakers as they always were
+    assert_eq!(face(Speaker::Ferris, Mouth::Neutral, None), face(Speaker::Ferris, Mouth::default(), None));
+    assert!(face(Speaker::Ferris, Mouth::Neutral, None).contains("'_   -   _'"));
+    assert!(face(Speaker::Ferris, Mouth::Smile, None).contains("'_   v   _'"));
+    assert!(face(Speaker::Ferris, Mouth::Frown, None).contains("'_   ^   _'"));
+    assert!(face(Speaker::Ferris, Mouth::TongueOut, None).contains("'_   P   _'"));
+    assert!(face(Speaker::Ferris, Mouth::Neutral, Some("U ")).contains("'_   -U  _'"));
+
+    assert!(face(Speaker::Clippy, Mouth::Neutral, None).contains("o  o\n           |  |"));
+    assert!(face(Speaker::Clippy, Mouth::Surprised, None).contains("o  o\n           |()|"));
+
+    assert!(face(Speaker::Cow, Mouth::Neutral, None).contains("(__)\\       )\\/\\\n                ||----w |"));
+    assert!(face(Speaker::Cow, Mouth::Open, None).contains("([])\\"));
+    assert!(face(Speaker::Cow, Mouth::TongueOut, None).contains("(__)\\       )\\/\\\n             U  ||----w |"));
+
+    // Speakers of your own keep their mouth unless told otherwise
+    let speaker_file = "mouth: w\ntongue: ~~\n---\n  {tail}\n   ({left_eye}{mouth}{right_eye})\n    {tongue}\n";
+    let speaker = Speaker::custom(SpeakerFile::parse("owo", speaker_file).unwrap());
+    assert!(face(speaker.clone(), Mouth::Neutral, None).contains("(owo)\n    ~~"));
+    assert!(face(speaker.clone(), Mouth::Surprised, None).contains("(ooo)\n    ~~"));
+    assert!(face(speaker, Mouth::TongueOut, None).contains("(owo)\n    U "));
+
+    assert_eq!(Mouth::all().iter().map(|mouth| mouth.name().parse()).collect::<Result<Vec<Mouth>, _>>().unwrap(), Mouth::all());
+    assert!("grin".parse::<Mouth>().is_err());
+    Ok(())
+}
+
+#[test]
+fn moods() -> Result<(), ()> {
+    let face = |speaker: Speaker, mood: Mood| {
+        let cfg = FerrisConfig { speaker, mood: Some(mood), eyes: Eyes::HappyEyes, ..Default::default() };
+        render(b"Hi", 40, &cfg).unwrap().lines().skip(5).collect::<Vec<_>>().join("\n")
+    };
+
+    // The cow makes the same faces as in cowsay, the mood wins over the eyes
+    assert_eq!(face(Speaker::Cow, Mood::Borg), concat!(
+        "            ^__^\n",
+        "            (==)\\_______\n",
+        "            (__)\\       )\\/\\\n",
+        "                ||----w |\n",
+        "                ||     ||",
+    ));
+    assert!(face(Speaker::Cow, Mood::Dead).contains("(xx)\\_______\n            (__)\\       )\\/\\\n             U  ||"));
+    assert!(face(Speaker::Cow, Mood::Stoned).contains("(**)"));
+    assert!(face(Speaker::Cow, Mood::Wired).contains("(OO)"));
+    assert!(face(Speaker::Cow, Mood::Youthful).contains("(..)"));
+
+    assert_eq!(face(Speaker::Ferris, Mood::Greedy), concat!(
+        "            _~^~^~_\n",
+        "        \\) /  $ $  \\ (/\n",
+        "          '_   -   _'\n",
+        "          / '-----' \\",
+    ));
+    assert!(face(Speaker::Ferris, Mood::Stoned).starts_with("            _~^~^~_ ~\n        \\) /  * *  \\ (/\n          '_   P   _'"));
+    assert!(face(Speaker::Clippy, Mood::Wired).starts_with("            __ !\n"));
+
+    // A tongue of your own still goes in
+    let cfg = FerrisConfig { speaker: Speaker::Cow, mood: Some(Mood::Dead), tongue: Some("V ".into()), ..Default::default() };
+    assert!(render(b"Hi", 40, &cfg).unwrap().lines().any(|line| line == "             V  ||----w |"));
+
+    // Ferris and ferris_say! take a mood as well
+    let dead = ferris_say!(speaker = Speaker::Cow, mood = Mood::Dead, "Hi").to_string();
+    let cfg = FerrisConfig { speaker: Speaker::Cow, mood: Some(Mood::Dead), ..Default::default() };
+    assert_eq!(dead, render(b"Hi", DEFAULT_WIDTH, &cfg).unwrap().text());
+
+    assert_eq!(Mood::all().iter().map(Mood::cowsay_flag).collect::<String>(), "bdgpstwy");
+    assert_eq!("paranoid".parse::<Mood>().unwrap(), Mood::Paranoid);
+    assert!("happy".parse::<Mood>().is_err());
+    Ok(())
+}
+
+#[test]
+fn errors() -> Result<(), ()> {
+    let cfg = FerrisConfig::default();
+    match render(b"Hello \xf0\x28 Rustaceans", DEFAULT_WIDTH, &cfg) {
+        Err(Error::InvalidUtf8 { offset, .. }) => assert_eq!(offset, 6),
+        other => panic!("expected invalid UTF-8, got {:?}", other),
+    }
+    match render(b"Hello", 0, &cfg) {
+        Err(Error::InvalidWidth(width)) => assert_eq!(width, 0),
+        other => panic!("expected an invalid width, got {:?}", other),
+    }
+
+    assert_eq!("clippy".parse::<Speaker>().unwrap().name(), "clippy");
+    match "nobody".parse::<Speaker>() {
+        Err(Error::UnknownSpeaker(name)) => assert_eq!(name, "nobody"),
+        _ => panic!("expected an unknown speaker"),
+    }
+    assert!("happy".parse::<Eyes>().is_ok());
+    match "sleepy".parse::<Eyes>() {
+        Err(Error::UnknownEyes(name)) => assert_eq!(name, "sleepy"),
+        other => panic!("expected unknown eyes, got {:?}", other.is_ok()),
+    }
+
+    let path = std::env::temp_dir().join("ferris-says-broken.cow");
+    std::fs::write(&path, "$the_cow = <<EOC;\n").unwrap();
+    let error = CowFile::open(&path).err().unwrap();
+    std::fs::remove_file(&path).unwrap();
+    match error {
+        Error::Cow { ref source, .. } => {
+            assert_eq!(*source, CowError::UnterminatedHeredoc { line: 1, terminator: String::from("EOC") })
+        }
+        ref other => panic!("expected a cow error, got {:?}", other),
+    }
+    assert!(std::error::Error::source(&error).is_some());
+
+    let io_error: std::io::Error = render(b"\xff", DEFAULT_WIDTH, &cfg).unwrap_err().into();
+    assert_eq!(io_error.kind(), std::io::ErrorKind::InvalidData);
+    assert!(matches!(Speaker::from_file("/does/not/exist.speaker"), Err(Error::Io(_))));
+    Ok(())
+}
+
+fn strip_escapes(text: &str) -> String {
+    let mut plain = String::new();
+    let mut rest = text;
+    while let Some(start) = rest.find('\x1b') {
+        plain.push_str(&rest[..start]);
+        let end = rest[start..].find('m').unwrap();
+        rest = &rest[start + end + 1..];
+    }
+    plain + rest
+}
+
+// The lines between the top and the bottom of the bubble
+fn bubble_lines(rendered: &Rendered) -> Vec<String> {
+    rendered.lines().take_while(|line| !line.starts_with(" -")).skip(1).map(String::from).collect()
+}
+
 fn create_ferris(
-    speech: String, top_part: &str, eye: &str, eye_gap: &str, bottom_part: &str
+    speech: String, thought: String, top_part: &str, eye: &str, eye_gap: &str, bottom_part: &str
 ) -> (String, String) {
-    let expected_say = speech.clone() + SPEECH_BUBBLE + top_part + eye +  eye_gap + eye + bottom_part;
-    let expected_think = speech.clone() + THOUGHT_BUBBLE + top_part + eye +  eye_gap + eye + bottom_part;
+    let expected_say = speech + SPEECH_BUBBLE + top_part + eye +  eye_gap + eye + bottom_part;
+    let expected_think = thought + THOUGHT_BUBBLE + top_part + eye +  eye_gap + eye + bottom_part;
     (expected_say, expected_think)
 }
 
-fn compare_strings_perform(
-    input: &[u8], width: usize, expected: &[u8], speaker: Speaker, cfg: &FerrisConfig
-) {
-    set_speaker(&speaker).unwrap();
+fn compare_strings_perform(input: &[u8], width: usize, expected: &[u8], cfg: &FerrisConfig) {
     let mut vec = Vec::new();
     perform(input, width, &mut vec, cfg).unwrap();
     let actual = std::str::from_utf8(&vec).unwrap();
-    println!("{}", std::str::from_utf8(&expected).unwrap());
+    println!("{}", std::str::from_utf8(expected).unwrap());
     println!("{}", actual);
-    assert_eq!(std::str::from_utf8(&expected).unwrap(), actual);
+    assert_eq!(std::str::from_utf8(expected).unwrap(), actual);
+    assert_eq!(render(input, width, cfg).unwrap().text(), actual);
 }
 
+#[allow(deprecated)]
 fn compare_strings_say_think(
     input: &[u8], width: usize, expected: &[u8],  speaker: Speaker, mode: &SpeechModes,eyes: &Eyes)
 {
-    set_speaker(&speaker).unwrap();
+    ferris_says::set_speaker(&speaker).unwrap();
     let mut vec = Vec::new();
     match mode {
         SpeechModes::Say => {
@@ -160,14 +1293,15 @@ fn compare_strings_say_think(
         SpeechModes::Think => {
             think(input, width, &mut vec, eyes).unwrap();
         }
+        _ => panic!("only saying and thinking have functions of their own"),
     };
     let actual = std::str::from_utf8(&vec).unwrap();
-    println!("{}", std::str::from_utf8(&expected).unwrap());
+    println!("{}", std::str::from_utf8(expected).unwrap());
     println!("{}", actual);
-    assert_eq!(std::str::from_utf8(&expected).unwrap(), actual);
+    assert_eq!(std::str::from_utf8(expected).unwrap(), actual);
 }
 
-fn generic_tests(speech: String, width: usize, input: &[u8]) {
+fn generic_tests(speech: String, thought: String, width: usize, input: &[u8]) {
     let top_ferris = std::str::from_utf8(FERRIS_TOP).unwrap();
     let bottom_ferris = std::str::from_utf8(FERRIS_BOTTOM).unwrap();
     let top_clippy = std::str::from_utf8(CLIPPY_TOP).unwrap();
@@ -175,70 +1309,66 @@ fn generic_tests(speech: String, width: usize, input: &[u8]) {
     let top_cow = std::str::from_utf8(COW_TOP).unwrap();
     let bottom_cow = std::str::from_utf8(COW_BOTTOM).unwrap();
     let (expected_say, expected_think) = create_ferris(
-        speech.clone(), top_ferris, "o", " ", bottom_ferris
+        speech.clone(), thought.clone(), top_ferris, "o", " ", bottom_ferris
     );
     let (happy_say, happy_think) = create_ferris(
-        speech.clone(), top_ferris, "^", " ", bottom_ferris
+        speech.clone(), thought.clone(), top_ferris, "^", " ", bottom_ferris
     );
     let (expected_say_clippy, expected_think_clippy) = create_ferris(
-        speech.clone(), top_clippy, "o", "  ", bottom_clippy
+        speech.clone(), thought.clone(), top_clippy, "o", "  ", bottom_clippy
     );
     let (happy_say_clippy, happy_think_clippy) = create_ferris(
-        speech.clone(), top_clippy, "^", "  ", bottom_clippy
+        speech.clone(), thought.clone(), top_clippy, "^", "  ", bottom_clippy
     );
     let (expected_say_cow, expected_think_cow) = create_ferris(
-        speech.clone(), top_cow, "o", "", bottom_cow
+        speech.clone(), thought.clone(), top_cow, "o", "", bottom_cow
     );
     let (happy_say_cow, happy_think_cow) = create_ferris(
-        speech.clone(), top_cow, "^", "", bottom_cow
+        speech.clone(), thought.clone(), top_cow, "^", "", bottom_cow
     );
-    let say = FerrisConfig {
+    let say = |speaker| FerrisConfig {
         mode: SpeechModes::Say,
-        eyes: Eyes::RegularEyes
+        eyes: Eyes::RegularEyes,
+        speaker,
+        ..Default::default()
     };
-    let think = FerrisConfig {
+    let think = |speaker| FerrisConfig {
         mode: SpeechModes::Think,
-        eyes: Eyes::RegularEyes
+        eyes: Eyes::RegularEyes,
+        speaker,
+        ..Default::default()
     };
 
-    compare_strings_perform(input, width, &expected_say.as_bytes(), Speaker::Ferris, &say);
-    compare_strings_perform(input, width, &expected_think.as_bytes(), Speaker::Ferris, &think);
+    compare_strings_perform(input, width, expected_say.as_bytes(), &say(Speaker::Ferris));
+    compare_strings_perform(input, width, expected_think.as_bytes(), &think(Speaker::Ferris));
     compare_strings_say_think(
-        input, width, &happy_say.as_bytes(), Speaker::Ferris, &SpeechModes::Say,
+        input, width, happy_say.as_bytes(), Speaker::Ferris, &SpeechModes::Say,
         &Eyes::HappyEyes
     );
     compare_strings_say_think(
-        input, width, &happy_think.as_bytes(), Speaker::Ferris, &SpeechModes::Think,
+        input, width, happy_think.as_bytes(), Speaker::Ferris, &SpeechModes::Think,
         &Eyes::HappyEyes
     );
 
-    compare_strings_perform(
-        input, width, &expected_say_clippy.as_bytes(), Speaker::Clippy, &say)
-        ;
-    compare_strings_perform(
-        input, width, &expected_think_clippy.as_bytes(), Speaker::Clippy, &think
-    );
+    compare_strings_perform(input, width, expected_say_clippy.as_bytes(), &say(Speaker::Clippy));
+    compare_strings_perform(input, width, expected_think_clippy.as_bytes(), &think(Speaker::Clippy));
     compare_strings_say_think(
-        input, width, &happy_say_clippy.as_bytes(), Speaker::Clippy, &SpeechModes::Say,
+        input, width, happy_say_clippy.as_bytes(), Speaker::Clippy, &SpeechModes::Say,
         &Eyes::HappyEyes
     );
     compare_strings_say_think(
-        input, width, &happy_think_clippy.as_bytes(), Speaker::Clippy, &SpeechModes::Think,
+        input, width, happy_think_clippy.as_bytes(), Speaker::Clippy, &SpeechModes::Think,
         &Eyes::HappyEyes
     );
 
-    compare_strings_perform(
-        input, width, &expected_say_cow.as_bytes(), Speaker::Cow, &say)
-        ;
-    compare_strings_perform(
-        input, width, &expected_think_cow.as_bytes(), Speaker::Cow, &think
-    );
+    compare_strings_perform(input, width, expected_say_cow.as_bytes(), &say(Speaker::Cow));
+    compare_strings_perform(input, width, expected_think_cow.as_bytes(), &think(Speaker::Cow));
     compare_strings_say_think(
-        input, width, &happy_say_cow.as_bytes(), Speaker::Cow, &SpeechModes::Say,
+        input, width, happy_say_cow.as_bytes(), Speaker::Cow, &SpeechModes::Say,
         &Eyes::HappyEyes
     );
     compare_strings_say_think(
-        input, width, &happy_think_cow.as_bytes(), Speaker::Cow, &SpeechModes::Think,
+        input, width, happy_think_cow.as_bytes(), Speaker::Cow, &SpeechModes::Think,
         &Eyes::HappyEyes
     );
 }